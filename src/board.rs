@@ -0,0 +1,88 @@
+//! The side-by-side columns of the `board` command.
+
+use nu_ansi_term::Style;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{calendar::weight_color, repo::Item};
+
+const SEPARATOR: &str = " │ ";
+
+/// A board column: its status, the cards shown and how many were left out.
+pub struct Column<'a> {
+    pub status: &'a str,
+    pub items: Vec<&'a Item>,
+    pub hidden: usize,
+}
+
+/// Renders the columns side by side, splitting `width` evenly between them.
+pub fn render(columns: &[Column], width: usize) -> String {
+    let separators = SEPARATOR.chars().count() * columns.len().saturating_sub(1);
+    let column_width = (width.saturating_sub(separators) / columns.len().max(1)).max(8);
+
+    let cells: Vec<Vec<(String, Style)>> = columns
+        .iter()
+        .map(|column| {
+            let bold = Style::new().bold();
+            let mut cells = vec![
+                (
+                    format!("{} ({})", column.status, column.items.len() + column.hidden),
+                    bold,
+                ),
+                ("─".repeat(column_width), Style::new()),
+            ];
+            for item in &column.items {
+                cells.push((item.name.clone(), Style::new()));
+                let mut details = item.weight.to_string();
+                if let Some(deadline) = item.deadline.filter(|_| !item.completed) {
+                    details.push_str(&format!(" · due {}", deadline.format("%b %d")));
+                }
+                cells.push((details, Style::new().fg(weight_color(item.weight))));
+                cells.push((String::new(), Style::new()));
+            }
+            if column.hidden > 0 {
+                cells.push((format!("+{} more", column.hidden), Style::new().italic()));
+            }
+            cells
+        })
+        .collect();
+
+    let rows = cells.iter().map(Vec::len).max().unwrap_or(0);
+    let mut lines = Vec::new();
+    for row in 0..rows {
+        let line: Vec<String> = cells
+            .iter()
+            .map(|column| {
+                let (text, style) = column
+                    .get(row)
+                    .cloned()
+                    .unwrap_or((String::new(), Style::new()));
+                let text = truncate(&text, column_width);
+                let padding = column_width - text.width();
+                format!("{}{}", style.paint(text), " ".repeat(padding))
+            })
+            .collect();
+        lines.push(line.join(SEPARATOR).trim_end().to_string());
+    }
+
+    lines.join("\n")
+}
+
+/// Cuts `text` to at most `width` columns, ending it with `…` if cut.
+fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+
+    let mut truncated = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if used + char_width + 1 > width {
+            break;
+        }
+        truncated.push(c);
+        used += char_width;
+    }
+    truncated.push('…');
+    truncated
+}
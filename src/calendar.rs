@@ -0,0 +1,131 @@
+//! The month grid of the `calendar` command and the day-by-day plan of
+//! `agenda`, both built from pending items' start dates and deadlines.
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Days, Duration, Local, NaiveDate};
+use nu_ansi_term::{Color, Style};
+
+use crate::{cli::Weight, repo::Item};
+
+/// Why an item shows up on a day of the agenda.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Event {
+    Due,
+    Starts,
+}
+
+pub struct Agenda<'a> {
+    /// Pending items whose deadline has passed, most overdue first
+    pub overdue: Vec<&'a Item>,
+    /// The days that have something on them, in order
+    pub days: BTreeMap<NaiveDate, Vec<(Event, &'a Item)>>,
+}
+
+pub fn weight_color(weight: Weight) -> Color {
+    match weight {
+        Weight::High => Color::Red,
+        Weight::Medium => Color::Yellow,
+        Weight::Low => Color::Green,
+    }
+}
+
+/// Renders the month starting on `first` as a Monday-first grid. Days with
+/// pending deadlines are colored by the highest weight due that day and
+/// today is underlined.
+pub fn month(items: &[Item], first: NaiveDate, today: NaiveDate) -> String {
+    let mut due: BTreeMap<NaiveDate, Weight> = BTreeMap::new();
+    for item in items.iter().filter(|item| !item.completed) {
+        if let Some(deadline) = item.deadline {
+            let weight = due.entry(deadline.date_naive()).or_insert(item.weight);
+            *weight = (*weight).max(item.weight);
+        }
+    }
+
+    let title = first.format("%B %Y").to_string();
+    let mut lines = vec![
+        format!("{:^27}", title).trim_end().to_string(),
+        " Mo  Tu  We  Th  Fr  Sa  Su".to_string(),
+    ];
+
+    let mut week = "    ".repeat(first.weekday().num_days_from_monday() as usize);
+    let mut date = first;
+    while date.month() == first.month() {
+        let mut style = match due.get(&date) {
+            Some(weight) => Style::new().bold().fg(weight_color(*weight)),
+            None => Style::new(),
+        };
+        if date == today {
+            style = style.underline();
+        }
+        week.push_str(&format!(" {} ", style.paint(format!("{:>2}", date.day()))));
+
+        if date.weekday().num_days_from_monday() == 6 {
+            lines.push(week.trim_end().to_string());
+            week.clear();
+        }
+        date += Duration::days(1);
+    }
+    if !week.is_empty() {
+        lines.push(week.trim_end().to_string());
+    }
+
+    lines.push(String::new());
+    lines.push(format!(
+        "Deadlines: {}  {}  {}",
+        Color::Red.bold().paint("high"),
+        Color::Yellow.bold().paint("medium"),
+        Color::Green.bold().paint("low"),
+    ));
+
+    lines.join("\n")
+}
+
+/// Collects what's due or starting on each of the `days` days from `today`,
+/// along with everything already overdue.
+pub fn agenda(items: &[Item], today: NaiveDate, days: u32) -> Agenda<'_> {
+    let now = Local::now();
+    // The day after the range, or none if that's past the last date there is
+    let end = today.checked_add_days(Days::new(days.into()));
+    let in_range = |date: NaiveDate| date >= today && end.is_none_or(|end| date < end);
+
+    let mut overdue: Vec<&Item> = items
+        .iter()
+        .filter(|item| !item.completed && item.deadline.is_some_and(|d| d < now))
+        .collect();
+    overdue.sort_by_key(|item| item.deadline);
+
+    let mut agenda: BTreeMap<NaiveDate, Vec<(Event, &Item)>> = BTreeMap::new();
+    for item in items.iter().filter(|item| !item.completed) {
+        if let Some(deadline) = item.deadline.filter(|d| *d >= now) {
+            if in_range(deadline.date_naive()) {
+                agenda
+                    .entry(deadline.date_naive())
+                    .or_default()
+                    .push((Event::Due, item));
+            }
+        }
+        if let Some(start_date) = item.start_date {
+            if in_range(start_date.date_naive()) {
+                agenda
+                    .entry(start_date.date_naive())
+                    .or_default()
+                    .push((Event::Starts, item));
+            }
+        }
+    }
+    for events in agenda.values_mut() {
+        events.sort_by_key(|(event, item)| {
+            let time = match event {
+                Event::Due => item.deadline,
+                Event::Starts => item.start_date,
+            };
+            (*event, time, std::cmp::Reverse(item.weight))
+        });
+    }
+
+    Agenda {
+        overdue,
+        days: agenda,
+    }
+}
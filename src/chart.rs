@@ -0,0 +1,112 @@
+//! Bar charts of open tasks and completions per day, for the `chart`
+//! command, reconstructed from `created_at` and `completed_at`. Removed
+//! tasks leave no history, so they don't show up in past days either.
+
+use chrono::{Duration, NaiveDate};
+
+use crate::repo::Item;
+
+const BLOCKS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// How many columns the y-axis labels take, including the separator.
+const AXIS_WIDTH: usize = 7;
+
+/// The number of open items at the end of each day from `since` to `until`.
+pub fn burndown(items: &[Item], since: NaiveDate, until: NaiveDate) -> Vec<(NaiveDate, usize)> {
+    days(since, until)
+        .map(|day| {
+            let open = items
+                .iter()
+                .filter(|item| item.created_at.date_naive() <= day)
+                .filter(|item| {
+                    !(item.completed
+                        && item
+                            .completed_at
+                            .is_some_and(|completed_at| completed_at.date_naive() <= day))
+                })
+                .count();
+            (day, open)
+        })
+        .collect()
+}
+
+/// The number of items completed on each day from `since` to `until`.
+pub fn throughput(items: &[Item], since: NaiveDate, until: NaiveDate) -> Vec<(NaiveDate, usize)> {
+    days(since, until)
+        .map(|day| {
+            let completed = items
+                .iter()
+                .filter(|item| item.completed)
+                .filter(|item| {
+                    item.completed_at
+                        .is_some_and(|completed_at| completed_at.date_naive() == day)
+                })
+                .count();
+            (day, completed)
+        })
+        .collect()
+}
+
+/// Renders a bar chart fitting in `width` by `height` characters. When
+/// there are more days than columns, neighbouring days share a bar, which
+/// shows their last value if `cumulative` or else their sum.
+pub fn render(
+    series: &[(NaiveDate, usize)],
+    width: usize,
+    height: usize,
+    cumulative: bool,
+) -> String {
+    let (Some((first, _)), Some((last, _))) = (series.first(), series.last()) else {
+        return String::new();
+    };
+
+    let columns = width.saturating_sub(AXIS_WIDTH).max(1);
+    let per_bar = series.len().div_ceil(columns);
+    let bars: Vec<usize> = series
+        .chunks(per_bar)
+        .map(|chunk| match cumulative {
+            true => chunk.last().map_or(0, |(_, value)| *value),
+            false => chunk.iter().map(|(_, value)| value).sum(),
+        })
+        .collect();
+    let bar_width = (columns / bars.len()).clamp(1, 3);
+
+    let rows = height.max(2);
+    let max = bars.iter().copied().max().unwrap_or(0).max(1);
+    let mut lines = Vec::new();
+    for row in (0..rows).rev() {
+        let label = match row {
+            _ if row == rows - 1 => max.to_string(),
+            0 => "0".to_string(),
+            _ => String::new(),
+        };
+        let mut line = format!("{:>5} │", label);
+        for value in &bars {
+            let eighths = (value * rows * 8).div_ceil(max) as isize - (row * 8) as isize;
+            let block = BLOCKS[eighths.clamp(0, 8) as usize];
+            line.extend(std::iter::repeat_n(block, bar_width));
+        }
+        lines.push(line.trim_end().to_string());
+    }
+
+    let axis_length = bars.len() * bar_width;
+    lines.push(format!("{:>5} └{}", "", "─".repeat(axis_length)));
+    let (first, last) = (first.format("%b %d"), last.format("%b %d"));
+    lines.push(format!(
+        "{:>6} {}{:>pad$}",
+        "",
+        first,
+        last,
+        pad = axis_length.saturating_sub(6)
+    ));
+    if per_bar > 1 {
+        lines.push(format!("{:>6} (one bar per {} days)", "", per_bar));
+    }
+
+    lines.join("\n")
+}
+
+fn days(since: NaiveDate, until: NaiveDate) -> impl Iterator<Item = NaiveDate> {
+    let count = (until - since).num_days().max(-1) + 1;
+    (0..count).map(move |offset| since + Duration::days(offset))
+}
@@ -1,7 +1,12 @@
+use chrono::{Duration, NaiveDate};
 use clap::{Parser, Subcommand, ValueEnum};
-use std::{fmt::Display, str::FromStr};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, path::PathBuf, str::FromStr};
 
-use crate::error::{Error, Result};
+use crate::{
+    duration,
+    error::{Error, Result},
+};
 
 /// A todo app
 #[derive(Parser, Debug)]
@@ -17,7 +22,8 @@ pub enum Command {
     /// Add a new todo item
     Add {
         /// The name of the todo item
-        name: String,
+        #[clap(required_unless_present = "full")]
+        name: Option<String>,
         /// The number of days to start the todo item
         #[clap(long)]
         days_to_start: Option<u32>,
@@ -30,6 +36,15 @@ pub enum Command {
         /// Optional description for the todo item
         #[clap(long)]
         description: Option<String>,
+        /// Estimated effort, as a duration (e.g. 3h) or story points (e.g. 5pt)
+        #[clap(long)]
+        estimate: Option<Estimate>,
+        /// Write the description in $VISUAL/$EDITOR
+        #[clap(long)]
+        editor: bool,
+        /// Edit every field as a front-matter document (with --editor)
+        #[clap(long, requires = "editor")]
+        full: bool,
     },
     /// Remove a todo item
     Remove {
@@ -55,12 +70,70 @@ pub enum Command {
         /// New deadline (days from now)
         #[clap(long)]
         days_to_complete: Option<u32>,
+        /// New effort estimate, as a duration (e.g. 3h) or story points (e.g. 5pt)
+        #[clap(long)]
+        estimate: Option<Estimate>,
+        /// Edit the description in $VISUAL/$EDITOR
+        #[clap(long)]
+        editor: bool,
+        /// Edit every field as a front-matter document (with --editor)
+        #[clap(long, requires = "editor")]
+        full: bool,
     },
     /// Mark a todo item as complete
     Complete {
         /// The name of the todo item to mark as complete
         name: String,
     },
+    /// Append, edit or delete notes on a todo item
+    Note {
+        /// The name of the todo item
+        name: String,
+        /// The text of the note
+        #[clap(required_unless_present = "delete")]
+        text: Option<String>,
+        /// Replace the note at this index instead of appending a new one
+        #[clap(long)]
+        edit: Option<usize>,
+        /// Delete the note at this index
+        #[clap(long, conflicts_with_all = ["text", "edit"])]
+        delete: Option<usize>,
+    },
+    /// Show every detail of a todo item along with its notes
+    Show {
+        /// The name or ID of the todo item to show
+        name: String,
+        /// The output format
+        #[clap(value_enum, long, default_value_t)]
+        format: OutputFormat,
+    },
+    /// Start a timer on a todo item
+    Start {
+        /// The name or ID of the todo item to work on
+        name: String,
+    },
+    /// Stop the running timer
+    Stop,
+    /// Log time spent on a todo item, e.g. 1h30m
+    LogTime {
+        /// The name or ID of the todo item
+        name: String,
+        /// How long was spent, using w/d/h/m units
+        #[clap(value_parser = duration::parse)]
+        duration: Duration,
+    },
+    /// Report tracked time grouped by period and task
+    Timesheet {
+        /// The period to group entries by
+        #[clap(value_enum, long, default_value_t)]
+        by: Period,
+        /// Only include entries started on or after this date (YYYY-MM-DD)
+        #[clap(long)]
+        since: Option<NaiveDate>,
+        /// Only include entries started on or before this date (YYYY-MM-DD)
+        #[clap(long)]
+        until: Option<NaiveDate>,
+    },
     /// List todo items
     List {
         /// Filter by weight
@@ -69,16 +142,251 @@ pub enum Command {
         /// Show only completed items
         #[clap(long)]
         completed: bool,
+        /// How to sort the items
+        #[clap(value_enum, long, conflicts_with_all = ["sort_by_deadline", "sort_by_weight", "sort_by_estimate"])]
+        sort: Option<SortKey>,
         /// Sort by deadline
         #[clap(long)]
         sort_by_deadline: bool,
         /// Sort by weight
         #[clap(long)]
         sort_by_weight: bool,
+        /// Sort by estimate, smallest first
+        #[clap(long)]
+        sort_by_estimate: bool,
+        /// Only show items estimated at least this much (same unit only)
+        #[clap(long)]
+        min_estimate: Option<Estimate>,
+        /// Only show items estimated at most this much (same unit only)
+        #[clap(long)]
+        max_estimate: Option<Estimate>,
+        /// Only show open items in this quadrant of the Eisenhower matrix
+        #[clap(value_enum, long)]
+        quadrant: Option<Quadrant>,
     },
+    /// Compare estimates with actual time for completed items
+    Estimates,
+    /// Show tasks as columns, one per workflow status
+    Board {
+        /// Only show tasks of this weight
+        #[clap(value_enum, long)]
+        weight: Option<Weight>,
+        /// How many done tasks to show, most recently completed first
+        #[clap(long, default_value_t = 10)]
+        done_limit: usize,
+    },
+    /// Move a task to another workflow status
+    Move {
+        /// The name or ID of the task
+        name: String,
+        /// The status to move it to
+        status: String,
+    },
+    /// Show the most urgent task that can be worked on now, and why
+    Next,
+    /// Sort open tasks into an Eisenhower matrix by weight and deadline
+    Matrix,
+    /// Open a full-screen interface to browse and change tasks
+    Tui,
+    /// Show previous REPL commands, oldest first
+    History {
+        /// Only show commands containing this text
+        search: Option<String>,
+    },
+    /// Show a month with the days that have deadlines highlighted
+    Calendar {
+        /// The month to show (YYYY-MM), this month by default
+        #[clap(long, value_parser = parse_month)]
+        month: Option<NaiveDate>,
+    },
+    /// List overdue tasks, then what is due or starting on each coming day
+    Agenda {
+        /// How many days to look ahead, including today
+        #[clap(long, default_value_t = 7)]
+        days: u32,
+    },
+    /// Chart open tasks or completions per day
+    Chart {
+        /// The chart to draw
+        #[clap(value_enum)]
+        kind: ChartKind,
+        /// The first day to chart (YYYY-MM-DD), 30 days ago by default
+        #[clap(long)]
+        since: Option<NaiveDate>,
+        /// The last day to chart (YYYY-MM-DD), today by default
+        #[clap(long)]
+        until: Option<NaiveDate>,
+        /// Only chart tasks of this weight
+        #[clap(value_enum, long)]
+        weight: Option<Weight>,
+    },
+    /// Report throughput, completion times, deadlines, backlog and streaks
+    Stats {
+        /// Only count tasks created or completed on or after this date (YYYY-MM-DD)
+        #[clap(long)]
+        since: Option<NaiveDate>,
+        /// Only count tasks created or completed on or before this date (YYYY-MM-DD)
+        #[clap(long)]
+        until: Option<NaiveDate>,
+        /// The output format
+        #[clap(value_enum, long, default_value_t)]
+        format: OutputFormat,
+    },
+    /// Export every todo item
+    Export {
+        /// The file format to export
+        #[clap(value_enum, long, default_value_t)]
+        format: FileFormat,
+        /// Write to this file instead of stdout
+        #[clap(long, short)]
+        output: Option<PathBuf>,
+        /// Comma-separated fields to export as columns (csv only)
+        #[clap(long, value_delimiter = ',')]
+        columns: Vec<String>,
+        /// Group tasks under a heading per weight or status (markdown only)
+        #[clap(value_enum, long)]
+        group_by: Option<GroupBy>,
+    },
+    /// Import todo items from a file
+    Import {
+        /// The file to import
+        file: PathBuf,
+        /// The file format to import
+        #[clap(value_enum, long, default_value_t)]
+        format: FileFormat,
+        /// What to do when an imported item's name is already taken
+        #[clap(value_enum, long, default_value_t)]
+        on_conflict: Conflict,
+        /// Report what would change without changing anything
+        #[clap(long)]
+        dry_run: bool,
+        /// Comma-separated header=field pairs mapping columns to fields (csv only)
+        #[clap(long, value_delimiter = ',', value_parser = parse_mapping)]
+        map: Vec<(String, String)>,
+    },
+    /// Copy the database to a file, safely while it is in use
+    Backup {
+        /// The file to write the backup to
+        path: PathBuf,
+    },
+    /// Replace the database with a backup
+    Restore {
+        /// The backup to restore
+        path: PathBuf,
+    },
+    /// Check the database for corruption and values that can't be read
+    Doctor {
+        /// Fix the values that can't be read, after taking a backup
+        #[clap(long)]
+        repair: bool,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FileFormat {
+    #[default]
+    Json,
+    Csv,
+    #[value(name = "todo.txt")]
+    TodoTxt,
+    Ics,
+    Taskwarrior,
+    Markdown,
+    Org,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChartKind {
+    /// Open tasks at the end of each day
+    Burndown,
+    /// Tasks completed on each day
+    Throughput,
+}
+
+/// How `list` orders its items.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortKey {
+    /// Newest first
+    Created,
+    Deadline,
+    Weight,
+    /// Smallest first
+    Estimate,
+    /// Most urgent first, by the configurable urgency score
+    Urgency,
+}
+
+/// A quadrant of the Eisenhower matrix.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Quadrant {
+    /// Urgent and important
+    Do,
+    /// Important but not urgent
+    Schedule,
+    /// Urgent but not important
+    Delegate,
+    /// Neither urgent nor important
+    Eliminate,
+}
+
+impl Display for Quadrant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Quadrant::Do => write!(f, "do"),
+            Quadrant::Schedule => write!(f, "schedule"),
+            Quadrant::Delegate => write!(f, "delegate"),
+            Quadrant::Eliminate => write!(f, "eliminate"),
+        }
+    }
+}
+
+/// How a Markdown export groups its tasks.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GroupBy {
+    Weight,
+    Status,
+}
+
+/// Parses `YYYY-MM` as the first day of that month.
+fn parse_month(s: &str) -> std::result::Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(&format!("{}-01", s.trim()), "%Y-%m-%d")
+        .map_err(|_| format!("expected YYYY-MM, got '{}'", s))
+}
+
+fn parse_mapping(s: &str) -> std::result::Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(header, field)| (header.trim().to_string(), field.trim().to_string()))
+        .ok_or_else(|| format!("expected <header>=<field>, got '{}'", s))
 }
 
-#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+/// How an import treats items whose name already exists.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Conflict {
+    /// Keep the existing item and drop the imported one
+    #[default]
+    Skip,
+    /// Replace the existing item's fields with the imported ones
+    Overwrite,
+    /// Import the item under a new name, e.g. "name (2)"
+    Rename,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Period {
+    #[default]
+    Day,
+    Week,
+}
+
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
 pub enum Weight {
     Low,
     Medium,
@@ -107,3 +415,58 @@ impl FromStr for Weight {
         }
     }
 }
+
+/// The expected size of a todo item, as a duration or in story points.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Estimate {
+    Time(Duration),
+    Points(u32),
+}
+
+impl Display for Estimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Estimate::Time(duration) => write!(f, "{}", duration::format_exact(*duration)),
+            Estimate::Points(points) => write!(f, "{}pt", points),
+        }
+    }
+}
+
+impl FromStr for Estimate {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let points = ["points", "point", "pts", "pt", "p"]
+            .iter()
+            .find_map(|suffix| s.strip_suffix(suffix))
+            .unwrap_or(s)
+            .trim();
+
+        match points.parse() {
+            Ok(points) => Ok(Estimate::Points(points)),
+            Err(_) => duration::parse(s)
+                .map(Estimate::Time)
+                .map_err(|_| Error::EstimateParse(s.to_string())),
+        }
+    }
+}
+
+impl Serialize for Estimate {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Estimate {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,182 @@
+//! Tab completion for the REPL. Subcommands, flags and their values come
+//! from the clap definitions in [`cli`](crate::cli), so they stay in sync with
+//! what the parser accepts; task names and IDs come from the repo.
+
+use std::sync::{Arc, Mutex};
+
+use clap::{Arg, Command as ClapCommand};
+use reedline::{Completer, Span, Suggestion};
+
+use crate::{
+    repo::Item,
+    syntax::{self, Role, Word},
+};
+
+/// The tasks to complete names and IDs from, shared with the REPL loop,
+/// which refreshes them after every command.
+pub type Tasks = Arc<Mutex<Vec<(i64, String)>>>;
+
+/// Replaces the tasks offered by completers sharing `tasks`.
+pub fn refresh(tasks: &Tasks, items: &[Item]) {
+    let mut tasks = tasks.lock().expect("no panics while holding the lock");
+    *tasks = items
+        .iter()
+        .map(|item| (item.id, item.name.clone()))
+        .collect();
+}
+
+pub struct TodoCompleter {
+    command: ClapCommand,
+    tasks: Tasks,
+    statuses: Vec<String>,
+}
+
+impl TodoCompleter {
+    pub fn new(tasks: Tasks, statuses: Vec<String>) -> Self {
+        Self {
+            command: syntax::command(),
+            tasks,
+            statuses,
+        }
+    }
+
+    fn subcommands(&self, word: &str, span: Span) -> Vec<Suggestion> {
+        self.command
+            .get_subcommands()
+            .filter(|command| command.get_name().starts_with(word))
+            .map(|command| suggestion(command.get_name(), command.get_about(), span))
+            .collect()
+    }
+
+    fn flags(command: &ClapCommand, word: &str, span: Span) -> Vec<Suggestion> {
+        command
+            .get_arguments()
+            .filter_map(|arg| Some((arg.get_long()?, arg)))
+            .map(|(long, arg)| (format!("--{}", long), arg))
+            .filter(|(flag, _)| flag.starts_with(word))
+            .map(|(flag, arg)| suggestion(&flag, arg.get_help(), span))
+            .collect()
+    }
+
+    /// Suggests values for `arg`: its possible values if it has any, or else
+    /// task names, IDs where the command resolves them, or workflow statuses
+    /// for the arguments that take them.
+    fn values(&self, command: &ClapCommand, arg: &Arg, word: &str, span: Span) -> Vec<Suggestion> {
+        let possible_values = arg.get_possible_values();
+        if !possible_values.is_empty() {
+            return possible_values
+                .iter()
+                .filter(|value| value.get_name().starts_with(word))
+                .map(|value| suggestion(value.get_name(), value.get_help(), span))
+                .collect();
+        }
+
+        let partial = word.to_lowercase();
+        match (command.get_name(), arg.get_id().as_str()) {
+            ("move", "status") => self
+                .statuses
+                .iter()
+                .filter(|status| status.to_lowercase().starts_with(&partial))
+                .map(|status| suggestion(&quote(status), None, span))
+                .collect(),
+            _ if syntax::takes_task(command, arg) => {
+                let tasks = self.tasks.lock().expect("no panics while holding the lock");
+                let ids = tasks
+                    .iter()
+                    .filter(|_| syntax::takes_id(command))
+                    .filter(|_| !partial.is_empty() && partial.chars().all(|c| c.is_ascii_digit()))
+                    .filter(|(id, _)| id.to_string().starts_with(&partial))
+                    .map(|(id, name)| Suggestion {
+                        description: Some(name.clone()),
+                        ..suggestion(&id.to_string(), None, span)
+                    });
+                let names = tasks
+                    .iter()
+                    .filter(|(_, name)| name.to_lowercase().starts_with(&partial))
+                    .map(|(_, name)| suggestion(&quote(name), None, span));
+                ids.chain(names).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl Completer for TodoCompleter {
+    fn complete(&mut self, line: &str, pos: usize) -> Vec<Suggestion> {
+        let mut words = syntax::words(&line[..pos]);
+        if words.last().is_none_or(|word| word.end < pos) {
+            words.push(Word {
+                start: pos,
+                end: pos,
+                text: String::new(),
+                quoted: false,
+                closed: true,
+            });
+        }
+        let roles = syntax::roles(&self.command, &words);
+        let (Some(word), Some(role)) = (words.last(), roles.last()) else {
+            return Vec::new();
+        };
+        let span = Span::new(word.start, pos);
+
+        match *role {
+            Role::Subcommand(_) => self.subcommands(&word.text, span),
+            Role::Flag(command, _) => Self::flags(command, &word.text, span),
+            Role::Value(command, arg) => self.values(command, arg, &word.text, span),
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn suggestion(value: &str, help: Option<&clap::builder::StyledStr>, span: Span) -> Suggestion {
+    Suggestion {
+        value: value.to_string(),
+        description: help.map(|help| help.to_string()),
+        style: None,
+        extra: None,
+        span,
+        append_whitespace: true,
+    }
+}
+
+/// Quotes `text` so that `shlex` reads it back as one word.
+fn quote(text: &str) -> String {
+    shlex::try_quote(text).map_or_else(|_| text.to_string(), |quoted| quoted.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(line: &str) -> Vec<String> {
+        let tasks = Tasks::default();
+        tasks
+            .lock()
+            .unwrap()
+            .extend([(12, "12 monkeys".to_string()), (1, "Milk".to_string())]);
+        let mut completer = TodoCompleter::new(tasks, Vec::new());
+        completer
+            .complete(line, line.len())
+            .into_iter()
+            .map(|suggestion| suggestion.value)
+            .collect()
+    }
+
+    #[test]
+    fn offers_ids_where_the_command_resolves_them() {
+        for command in ["show", "start", "log-time", "move"] {
+            assert_eq!(
+                values(&format!("{} 1", command)),
+                ["12", "1", "'12 monkeys'"]
+            );
+        }
+    }
+
+    #[test]
+    fn offers_only_names_where_the_command_matches_names() {
+        for command in ["remove", "complete", "edit", "note"] {
+            assert_eq!(values(&format!("{} 1", command)), ["'12 monkeys'"]);
+            assert_eq!(values(&format!("{} m", command)), ["Milk"]);
+        }
+    }
+}
@@ -0,0 +1,318 @@
+//! Settings read from `~/.todo/config.toml`. Every setting is optional, e.g.
+//!
+//! ```toml
+//! [workflow]
+//! statuses = ["backlog", "started", "review", "done"]
+//!
+//! [matrix]
+//! important = "medium"
+//! urgent_days = 3
+//!
+//! [urgency]
+//! high = 8.0
+//! overdue = 5.0
+//!
+//! [history]
+//! size = 5000
+//!
+//! [prompt]
+//! right = "{open} open, {today} due today {timer}"
+//! ```
+
+use std::fs;
+
+use chrono::{DateTime, Duration, Local};
+use serde::Deserialize;
+
+use crate::{
+    cli::{Quadrant, Weight},
+    duration,
+    error::{Error, Result},
+    prompt::{self, PLACEHOLDERS},
+    repo::Item,
+};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub workflow: Workflow,
+    pub matrix: Matrix,
+    pub urgency: Urgency,
+    pub history: History,
+    pub prompt: Prompt,
+}
+
+impl Config {
+    /// Reads the config file, or returns the defaults if there is none.
+    pub fn load() -> Result<Self> {
+        let home_dir = dirs::home_dir().ok_or(Error::HomeDirNotFound)?;
+        let path = home_dir.join(".todo").join("config.toml");
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let text = fs::read_to_string(&path)?;
+        let config: Config = toml::from_str(&text)
+            .map_err(|e| Error::Config(format!("{}: {}", path.display(), e)))?;
+        config.workflow.validate()?;
+        config.prompt.validate()?;
+        Ok(config)
+    }
+}
+
+/// The statuses a task moves through, in board order. The last one means
+/// done and matches the `completed` flag.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Workflow {
+    pub statuses: Vec<String>,
+}
+
+impl Default for Workflow {
+    fn default() -> Self {
+        Self {
+            statuses: ["backlog", "started", "done"].map(String::from).to_vec(),
+        }
+    }
+}
+
+impl Workflow {
+    fn validate(&self) -> Result<()> {
+        if self.statuses.len() < 2 {
+            return Err(Error::Config(
+                "workflow needs at least two statuses".to_string(),
+            ));
+        }
+        for (index, status) in self.statuses.iter().enumerate() {
+            if status.trim().is_empty() {
+                return Err(Error::Config(
+                    "workflow statuses can't be empty".to_string(),
+                ));
+            }
+            if self.statuses[..index].contains(status) {
+                return Err(Error::Config(format!(
+                    "workflow status listed twice: {}",
+                    status
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn done(&self) -> &str {
+        self.statuses
+            .last()
+            .expect("workflows have at least two statuses")
+    }
+
+    /// Looks a status up, ignoring case.
+    pub fn find(&self, status: &str) -> Result<&str> {
+        self.statuses
+            .iter()
+            .find(|s| s.eq_ignore_ascii_case(status.trim()))
+            .map(String::as_str)
+            .ok_or_else(|| Error::UnknownStatus(status.to_string(), self.statuses.join(", ")))
+    }
+
+    /// The status an item is in. Completed items are done; other items are in
+    /// the status they were last moved to, or else in the first status until
+    /// their start date passes and in the second one after, unless that is
+    /// already done.
+    pub fn status_of<'a>(&'a self, item: &Item) -> &'a str {
+        if item.completed {
+            return self.done();
+        }
+        if let Some(status) = item.status.as_deref().and_then(|s| self.find(s).ok()) {
+            if status != self.done() {
+                return status;
+            }
+        }
+
+        let started = item.start_date.is_some_and(|start| start <= Local::now());
+        match self.statuses.len() {
+            3.. if started => &self.statuses[1],
+            _ => &self.statuses[0],
+        }
+    }
+}
+
+/// Where the Eisenhower matrix draws the line between important and not,
+/// and between urgent and not.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Matrix {
+    /// The lowest weight that counts as important
+    pub important: Weight,
+    /// Deadlines at most this many days away, or past, count as urgent
+    pub urgent_days: u32,
+}
+
+impl Default for Matrix {
+    fn default() -> Self {
+        Self {
+            important: Weight::High,
+            urgent_days: 2,
+        }
+    }
+}
+
+impl Matrix {
+    /// The quadrant an open item falls in, or `None` for completed items.
+    pub fn quadrant(&self, item: &Item, now: DateTime<Local>) -> Option<Quadrant> {
+        if item.completed {
+            return None;
+        }
+
+        let important = item.weight >= self.important;
+        let urgent = item
+            .deadline
+            .is_some_and(|deadline| deadline - now <= Duration::days(self.urgent_days as i64));
+        Some(match (urgent, important) {
+            (true, true) => Quadrant::Do,
+            (false, true) => Quadrant::Schedule,
+            (true, false) => Quadrant::Delegate,
+            (false, false) => Quadrant::Eliminate,
+        })
+    }
+}
+
+/// Coefficients of the urgency score, which adds up one term per property
+/// of an item. Scaled terms multiply their coefficient by a factor from 0 to 1.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Urgency {
+    pub high: f64,
+    pub medium: f64,
+    pub low: f64,
+    /// Scaled by how close the deadline is: 0.2 two weeks out, rising to 1
+    /// a week after it has passed
+    pub deadline: f64,
+    /// Added once the deadline has passed
+    pub overdue: f64,
+    /// Scaled by age, reaching 1 after a year
+    pub age: f64,
+    /// Added for items that have started, by start date or workflow status
+    pub started: f64,
+    /// Added for items whose start date is still ahead, usually negative
+    pub waiting: f64,
+}
+
+impl Default for Urgency {
+    fn default() -> Self {
+        Self {
+            high: 6.0,
+            medium: 3.9,
+            low: 1.8,
+            deadline: 12.0,
+            overdue: 3.0,
+            age: 2.0,
+            started: 4.0,
+            waiting: -3.0,
+        }
+    }
+}
+
+/// An urgency score and the terms it adds up.
+#[derive(Debug)]
+pub struct Score {
+    pub total: f64,
+    /// A description of each term with its contribution
+    pub terms: Vec<(String, f64)>,
+}
+
+impl Urgency {
+    pub fn score(&self, item: &Item, workflow: &Workflow, now: DateTime<Local>) -> Score {
+        let mut terms = Vec::new();
+
+        let weight = match item.weight {
+            Weight::High => self.high,
+            Weight::Medium => self.medium,
+            Weight::Low => self.low,
+        };
+        terms.push((format!("{} weight", item.weight), weight));
+
+        if let Some(deadline) = item.deadline {
+            let days = (deadline - now).num_minutes() as f64 / (24.0 * 60.0);
+            let proximity = ((14.0 - days) * 0.8 / 21.0 + 0.2).clamp(0.2, 1.0);
+            let when = match deadline - now {
+                left if left >= Duration::zero() => format!("due in {}", duration::format(left)),
+                late => format!("due {} ago", duration::format(late)),
+            };
+            terms.push((
+                format!("{} ({:.2} × {})", when, proximity, self.deadline),
+                proximity * self.deadline,
+            ));
+            if deadline < now {
+                terms.push(("overdue".to_string(), self.overdue));
+            }
+        }
+
+        let age = item.age();
+        let factor = (age.num_days() as f64 / 365.0).clamp(0.0, 1.0);
+        terms.push((
+            format!(
+                "{} old ({:.2} × {})",
+                duration::format(age),
+                factor,
+                self.age
+            ),
+            factor * self.age,
+        ));
+
+        let status = workflow.status_of(item);
+        if status != workflow.statuses[0] && status != workflow.done() {
+            terms.push((format!("status: {}", status), self.started));
+        } else if item.start_date.is_some_and(|start| start > now) {
+            terms.push(("starts later".to_string(), self.waiting));
+        }
+
+        Score {
+            total: terms.iter().map(|(_, value)| value).sum(),
+            terms,
+        }
+    }
+}
+
+/// How the REPL keeps its command history.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct History {
+    /// The most commands kept; older ones are dropped
+    pub size: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self { size: 1000 }
+    }
+}
+
+/// Templates for the REPL prompt; see [`prompt::PLACEHOLDERS`] for what
+/// they can show.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Prompt {
+    pub left: String,
+    pub right: String,
+}
+
+impl Default for Prompt {
+    fn default() -> Self {
+        Self {
+            left: "todo".to_string(),
+            right: "{open} open · {overdue} overdue {timer}".to_string(),
+        }
+    }
+}
+
+impl Prompt {
+    fn validate(&self) -> Result<()> {
+        for template in [&self.left, &self.right] {
+            prompt::expand(template, |placeholder| {
+                PLACEHOLDERS.contains(&placeholder).then(String::new)
+            })
+            .map_err(Error::Config)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+use chrono::Duration;
+
+use crate::error::{Error, Result};
+
+/// Parses durations such as `1h30m`, `45m`, `2d` or `1w 2d`.
+pub fn parse(s: &str) -> Result<Duration> {
+    let mut total = Duration::zero();
+    let mut digits = String::new();
+    let mut has_unit = false;
+
+    for c in s.chars().filter(|c| !c.is_whitespace()) {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+
+        let value: i64 = digits
+            .parse()
+            .map_err(|_| Error::DurationParse(s.to_string()))?;
+        digits.clear();
+        let part = match c.to_ascii_lowercase() {
+            'w' => Duration::try_weeks(value),
+            'd' => Duration::try_days(value),
+            'h' => Duration::try_hours(value),
+            'm' => Duration::try_minutes(value),
+            _ => None,
+        };
+        total = part
+            .and_then(|part| total.checked_add(&part))
+            .ok_or_else(|| Error::DurationParse(s.to_string()))?;
+        has_unit = true;
+    }
+
+    if !digits.is_empty() || !has_unit {
+        return Err(Error::DurationParse(s.to_string()));
+    }
+
+    Ok(total)
+}
+
+/// Adds `b` to `a`, stopping at the largest duration rather than overflowing.
+pub fn saturating_add(a: Duration, b: Duration) -> Duration {
+    a.checked_add(&b).unwrap_or(Duration::MAX)
+}
+
+/// Formats a duration using its two most significant units, e.g. `3d 4h`.
+pub fn format(duration: Duration) -> String {
+    let minutes = duration.num_minutes().abs();
+    if minutes == 0 {
+        return "<1m".to_string();
+    }
+
+    let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+    let parts: Vec<String> = [(days, "d"), (hours, "h"), (minutes, "m")]
+        .into_iter()
+        .skip_while(|(value, _)| *value == 0)
+        .take(2)
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{}{}", value, unit))
+        .collect();
+
+    parts.join(" ")
+}
+
+/// Formats a duration with every non-zero unit, e.g. `1d 2h 30m`, so that it
+/// parses back to the same number of minutes.
+pub fn format_exact(duration: Duration) -> String {
+    let minutes = duration.num_minutes().abs();
+    if minutes == 0 {
+        return "0m".to_string();
+    }
+
+    let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+    let parts: Vec<String> = [(days, "d"), (hours, "h"), (minutes, "m")]
+        .into_iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{}{}", value, unit))
+        .collect();
+
+    parts.join(" ")
+}
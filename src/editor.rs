@@ -0,0 +1,227 @@
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::{
+    collections::hash_map::RandomState,
+    env,
+    fs::{self, OpenOptions},
+    hash::{BuildHasher, Hasher},
+    io::{ErrorKind, Write},
+    path::PathBuf,
+    process::Command,
+};
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+
+use crate::{
+    cli::Weight,
+    error::{Error, Result},
+    repo::{Draft, Item},
+};
+
+const DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Lets the user edit `draft` in their editor and passes the result to
+/// `save`. With `full` the whole task is edited as a front-matter document,
+/// otherwise only the description.
+///
+/// Returns `false` if the user aborted by saving an empty or unchanged file,
+/// or one that reads back as the same task.
+/// If the document doesn't parse or `save` fails, the edited text is kept in
+/// `~/.todo/unsaved-edit.md` so it isn't lost.
+pub fn edit_draft(
+    draft: &mut Draft,
+    full: bool,
+    save: impl FnOnce(&Draft) -> Result<()>,
+) -> Result<bool> {
+    let initial = match full {
+        true => render(draft),
+        false => draft.description.clone().unwrap_or_default(),
+    };
+    let Some(text) = edit(&initial)? else {
+        return Ok(false);
+    };
+
+    let edited = match full {
+        true => parse(&text),
+        false => Ok(Draft {
+            description: Some(text.trim().to_string()),
+            ..draft.clone()
+        }),
+    };
+    if edited.as_ref().is_ok_and(|edited| edited == draft) {
+        return Ok(false);
+    }
+    match edited.and_then(|edited| save(&edited).map(|()| edited)) {
+        Ok(edited) => {
+            *draft = edited;
+            Ok(true)
+        }
+        Err(e) => Err(keep(&text, e)),
+    }
+}
+
+/// Writes `text` to `~/.todo/unsaved-edit.md` and adds where to `error`, or
+/// returns `error` as it is if the text can't be written.
+fn keep(text: &str, error: Error) -> Error {
+    let Some(home_dir) = dirs::home_dir() else {
+        return error;
+    };
+    let path = home_dir.join(".todo").join("unsaved-edit.md");
+    match fs::write(&path, text) {
+        Ok(()) => Error::EditKept(Box::new(error), path),
+        Err(_) => error,
+    }
+}
+
+/// Opens `$VISUAL` (falling back to `$EDITOR`, then `vi`) on a temp file
+/// pre-filled with `initial` and returns what was saved, or `None` if the
+/// file was left empty or unchanged apart from surrounding whitespace.
+pub fn edit(initial: &str) -> Result<Option<String>> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut args = shlex::split(&editor)
+        .filter(|args| !args.is_empty())
+        .ok_or_else(|| Error::Editor(format!("invalid editor command: {}", editor)))?;
+    let program = args.remove(0);
+
+    let path = create_temp_file(initial)?;
+
+    let status = Command::new(&program).args(args).arg(&path).status();
+    let contents = fs::read_to_string(&path);
+    fs::remove_file(&path)?;
+
+    let status = status.map_err(|e| Error::Editor(format!("{}: {}", program, e)))?;
+    if !status.success() {
+        return Err(Error::Editor(format!("{} exited with {}", program, status)));
+    }
+
+    let contents = contents?;
+    // Editors tend to add a trailing newline, which doesn't count as a change
+    if contents.trim().is_empty() || contents.trim() == initial.trim() {
+        Ok(None)
+    } else {
+        Ok(Some(contents))
+    }
+}
+
+/// Creates a file only this user can access under a random name in the temp
+/// dir, failing rather than following anything another user put in its place.
+fn create_temp_file(contents: &str) -> Result<PathBuf> {
+    let mut attempts = 0;
+    loop {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u32(std::process::id());
+        let path = env::temp_dir().join(format!("todo-{:016x}.md", hasher.finish()));
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        match options.open(&path) {
+            Ok(mut file) => {
+                file.write_all(contents.as_bytes())?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists && attempts < 10 => attempts += 1,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Renders a draft as a front-matter document, with the description as body.
+pub fn render(draft: &Draft) -> String {
+    format!(
+        "---\nname: {}\nweight: {}\nstart: {}\ndeadline: {}\nestimate: {}\n---\n{}\n",
+        draft.name,
+        draft.weight,
+        Item::format_date(draft.start_date),
+        Item::format_date(draft.deadline),
+        draft
+            .estimate
+            .map_or_else(|| "Not set".to_string(), |e| e.to_string()),
+        draft.description.as_deref().unwrap_or_default(),
+    )
+}
+
+/// Parses a document produced by [`render`] back into a draft.
+pub fn parse(text: &str) -> Result<Draft> {
+    let mut lines = text.lines();
+    if lines.next().map(str::trim) != Some("---") {
+        return Err(Error::Document("missing opening '---'".to_string()));
+    }
+
+    let mut name = None;
+    let mut weight = Weight::Medium;
+    let mut start_date = None;
+    let mut deadline = None;
+    let mut estimate = None;
+    let mut closed = false;
+
+    for line in lines.by_ref() {
+        let line = line.trim();
+        if line == "---" {
+            closed = true;
+            break;
+        }
+        if line.is_empty() {
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once(':')
+            .ok_or_else(|| Error::Document(format!("expected 'key: value', got '{}'", line)))?;
+        let value = value.trim();
+        match key.trim() {
+            "name" => name = Some(value.to_string()),
+            "weight" => weight = value.parse()?,
+            "start" => start_date = parse_date(value)?,
+            "deadline" => deadline = parse_date(value)?,
+            "estimate" if is_unset(value) => estimate = None,
+            "estimate" => estimate = Some(value.parse()?),
+            key => return Err(Error::Document(format!("unknown field '{}'", key))),
+        }
+    }
+
+    if !closed {
+        return Err(Error::Document("missing closing '---'".to_string()));
+    }
+
+    let name = name
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| Error::Document("name must not be empty".to_string()))?;
+    let description = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+
+    Ok(Draft {
+        name,
+        description: (!description.is_empty()).then_some(description),
+        weight,
+        start_date,
+        deadline,
+        estimate,
+    })
+}
+
+fn parse_date(value: &str) -> Result<Option<DateTime<Local>>> {
+    if is_unset(value) {
+        return Ok(None);
+    }
+
+    let naive = match NaiveDateTime::parse_from_str(value, DATE_TIME_FORMAT) {
+        Ok(naive) => naive,
+        Err(_) => NaiveDate::parse_from_str(value, DATE_FORMAT)?
+            .and_hms_opt(0, 0, 0)
+            .expect("midnight is a valid time"),
+    };
+
+    Local
+        .from_local_datetime(&naive)
+        .single()
+        .map(Some)
+        .ok_or_else(|| Error::Document(format!("ambiguous local time '{}'", value)))
+}
+
+fn is_unset(value: &str) -> bool {
+    value.is_empty() || value.eq_ignore_ascii_case("not set") || value.eq_ignore_ascii_case("none")
+}
@@ -6,12 +6,44 @@ pub enum Error {
     Database(#[from] rusqlite::Error),
     #[error("todo item not found: {0}")]
     ItemNotFound(String),
+    #[error("note {1} not found on: {0}")]
+    NoteNotFound(String, usize),
     #[error("invalid date format: {0}")]
     DateParse(#[from] chrono::ParseError),
+    #[error("unrecognized date: {0}")]
+    UnrecognizedDate(String),
     #[error("invalid weight format: {0}")]
     WeightParse(String),
+    #[error("invalid estimate format: {0}")]
+    EstimateParse(String),
+    #[error("invalid duration format: {0}")]
+    DurationParse(String),
+    #[error("a timer is already running for: {0}")]
+    TimerRunning(String),
+    #[error("no timer is running")]
+    NoTimerRunning,
+    #[error("invalid task document: {0}")]
+    Document(String),
+    #[error("editor failed: {0}")]
+    Editor(String),
+    #[error("{0}; your edit was kept in {path}", path = .1.display())]
+    EditKept(Box<Error>, std::path::PathBuf),
+    #[error("invalid config: {0}")]
+    Config(String),
+    #[error("unknown status: {0} (expected one of: {1})")]
+    UnknownStatus(String, String),
+    #[error("not a compatible todo database: {0}")]
+    IncompatibleDatabase(String),
     #[error("home directory not found")]
     HomeDirNotFound,
+    #[error("unknown field: {0}")]
+    UnknownField(String),
+    #[error("csv error: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("history error: {0}")]
+    History(#[from] reedline::ReedlineError),
     #[error("io error: {0}")]
     IO(#[from] std::io::Error),
 }
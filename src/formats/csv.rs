@@ -0,0 +1,159 @@
+use csv::{Reader, StringRecord, Writer};
+
+use super::{parse_date, RowError};
+use crate::{
+    error::{Error, Result},
+    repo::Item,
+};
+
+/// The item fields that can be exported as columns or mapped to from headers.
+pub const FIELDS: &[&str] = &[
+    "id",
+    "name",
+    "description",
+    "weight",
+    "start_date",
+    "deadline",
+    "completed",
+    "created_at",
+    "completed_at",
+    "estimate",
+];
+
+/// Writes one row per item with the given columns, or every field if
+/// `columns` is empty.
+pub fn export(items: &[Item], columns: &[String]) -> Result<String> {
+    let columns: Vec<&str> = if columns.is_empty() {
+        FIELDS.to_vec()
+    } else {
+        columns
+            .iter()
+            .map(|column| field_name(column))
+            .collect::<Result<_>>()?
+    };
+
+    let mut writer = Writer::from_writer(Vec::new());
+    writer.write_record(&columns)?;
+    for item in items {
+        writer.write_record(columns.iter().map(|column| field(item, column)))?;
+    }
+
+    let bytes = writer.into_inner().map_err(|e| Error::IO(e.into_error()))?;
+    Ok(String::from_utf8(bytes).expect("csv writer only writes utf-8 strings"))
+}
+
+/// Reads items from CSV with a header row. Headers are matched to fields by
+/// `mapping` (header, field) pairs first, then by name; unmatched columns are
+/// ignored. Rows that fail to parse are reported instead of aborting.
+pub fn import(text: &str, mapping: &[(String, String)]) -> Result<(Vec<Item>, Vec<RowError>)> {
+    let mut reader = Reader::from_reader(text.as_bytes());
+
+    let fields = reader
+        .headers()?
+        .iter()
+        .map(
+            |header| match mapping.iter().find(|(from, _)| from == header.trim()) {
+                Some((_, field)) => field_name(field).map(Some),
+                None => Ok(FIELDS
+                    .iter()
+                    .find(|field| field.eq_ignore_ascii_case(header.trim()))
+                    .copied()),
+            },
+        )
+        .collect::<Result<Vec<_>>>()?;
+    if !fields.contains(&Some("name")) {
+        return Err(Error::Document(
+            "no column maps to name; use --map <header>=name".to_string(),
+        ));
+    }
+
+    let mut items = Vec::new();
+    let mut errors = Vec::new();
+    for record in reader.records() {
+        let result = record.map_err(|e| {
+            let line = e.position().map_or(0, |position| position.line());
+            RowError::at_line(line, e.to_string())
+        });
+        let parsed = result.and_then(|record| {
+            let line = record.position().map_or(0, |position| position.line());
+            parse_row(&fields, &record).map_err(|message| RowError::at_line(line, message))
+        });
+
+        match parsed {
+            Ok(item) => items.push(item),
+            Err(error) => errors.push(error),
+        }
+    }
+
+    Ok((items, errors))
+}
+
+fn field_name(name: &str) -> Result<&'static str> {
+    FIELDS
+        .iter()
+        .find(|field| field.eq_ignore_ascii_case(name.trim()))
+        .copied()
+        .ok_or_else(|| Error::UnknownField(name.to_string()))
+}
+
+fn field(item: &Item, column: &str) -> String {
+    match column {
+        "id" => item.id.to_string(),
+        "name" => item.name.clone(),
+        "description" => item.description.clone().unwrap_or_default(),
+        "weight" => item.weight.to_string(),
+        "start_date" => item.start_date.map(|d| d.to_rfc3339()).unwrap_or_default(),
+        "deadline" => item.deadline.map(|d| d.to_rfc3339()).unwrap_or_default(),
+        "completed" => item.completed.to_string(),
+        "created_at" => item.created_at.to_rfc3339(),
+        "completed_at" => item
+            .completed_at
+            .map(|d| d.to_rfc3339())
+            .unwrap_or_default(),
+        "estimate" => item.estimate.map(|e| e.to_string()).unwrap_or_default(),
+        _ => unreachable!("columns are checked against FIELDS"),
+    }
+}
+
+fn parse_row(fields: &[Option<&str>], record: &StringRecord) -> std::result::Result<Item, String> {
+    let mut item = Item::new(String::new());
+
+    for (field, value) in fields.iter().zip(record.iter()) {
+        let (Some(field), value) = (field, value.trim()) else {
+            continue;
+        };
+        if value.is_empty() {
+            continue;
+        }
+
+        let invalid = |e: Error| format!("{}: {}", field, e);
+        match *field {
+            "id" => {
+                item.id = value
+                    .parse()
+                    .map_err(|_| format!("{}: not a number: {}", field, value))?
+            }
+            "name" => item.name = value.to_string(),
+            "description" => item.description = Some(value.to_string()),
+            "weight" => item.weight = value.parse().map_err(invalid)?,
+            "start_date" => item.start_date = Some(parse_date(value).map_err(invalid)?),
+            "deadline" => item.deadline = Some(parse_date(value).map_err(invalid)?),
+            "completed" => {
+                item.completed = match value.to_lowercase().as_str() {
+                    "true" | "yes" | "y" | "1" | "x" | "done" => true,
+                    "false" | "no" | "n" | "0" | "" => false,
+                    _ => return Err(format!("{}: not a boolean: {}", field, value)),
+                }
+            }
+            "created_at" => item.created_at = parse_date(value).map_err(invalid)?,
+            "completed_at" => item.completed_at = Some(parse_date(value).map_err(invalid)?),
+            "estimate" => item.estimate = Some(value.parse().map_err(invalid)?),
+            _ => unreachable!("fields are checked against FIELDS"),
+        }
+    }
+
+    if item.name.is_empty() {
+        return Err("name: missing".to_string());
+    }
+    Ok(item)
+}
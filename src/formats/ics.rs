@@ -0,0 +1,294 @@
+//! iCalendar (RFC 5545) `VTODO` components.
+//!
+//! Exported times are written in UTC. Imported times may be UTC, floating
+//! (read as local time), dates, or local to an IANA `TZID`; all of them end up
+//! in local time like the rest of the repo.
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use super::RowError;
+use crate::{cli::Weight, repo::Item};
+
+const DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%S";
+const DATE_FORMAT: &str = "%Y%m%d";
+const UID_DOMAIN: &str = "@todo";
+
+pub fn export(items: &[Item]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//todo//todo//EN".to_string(),
+    ];
+
+    let now = Local::now();
+    for item in items {
+        lines.push("BEGIN:VTODO".to_string());
+        lines.push(format!("UID:{}{}", item.id, UID_DOMAIN));
+        lines.push(format!("DTSTAMP:{}", date_time(now)));
+        lines.push(format!("CREATED:{}", date_time(item.created_at)));
+        lines.push(format!("SUMMARY:{}", escape(&item.name)));
+        if let Some(description) = &item.description {
+            lines.push(format!("DESCRIPTION:{}", escape(description)));
+        }
+        if let Some(start_date) = item.start_date {
+            lines.push(format!("DTSTART:{}", date_time(start_date)));
+        }
+        if let Some(deadline) = item.deadline {
+            lines.push(format!("DUE:{}", date_time(deadline)));
+        }
+        lines.push(format!("PRIORITY:{}", priority(item.weight)));
+        if item.completed {
+            lines.push("STATUS:COMPLETED".to_string());
+            if let Some(completed_at) = item.completed_at {
+                lines.push(format!("COMPLETED:{}", date_time(completed_at)));
+            }
+        } else {
+            lines.push("STATUS:NEEDS-ACTION".to_string());
+        }
+        if let Some(estimate) = item.estimate {
+            lines.push(format!("X-TODO-ESTIMATE:{}", estimate));
+        }
+        lines.push("END:VTODO".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+/// Reads every `VTODO` in the calendar. Components that fail to parse are
+/// reported, by the line they start on, instead of aborting.
+pub fn import(text: &str) -> (Vec<Item>, Vec<RowError>) {
+    let mut items = Vec::new();
+    let mut errors = Vec::new();
+    // The line the current VTODO starts on, its properties, and how deep
+    // into components nested in it, such as VALARMs, the line is
+    let mut todo: Option<(u64, Vec<Property>, usize)> = None;
+
+    for (line, property) in unfold(text) {
+        match (&mut todo, property.name.as_str(), property.value.as_str()) {
+            (None, "BEGIN", "VTODO") => todo = Some((line, Vec::new(), 0)),
+            (Some((_, _, depth)), "BEGIN", _) => *depth += 1,
+            (Some((start, properties, 0)), "END", "VTODO") => {
+                match parse_todo(properties) {
+                    Ok(item) => items.push(item),
+                    Err(message) => errors.push(RowError::at_line(*start, message)),
+                }
+                todo = None;
+            }
+            (Some((_, _, depth)), "END", _) if *depth > 0 => *depth -= 1,
+            // Nested components' properties aren't the task's
+            (Some((_, properties, 0)), _, _) => properties.push(property),
+            (Some(_), _, _) | (None, _, _) => {}
+        }
+    }
+
+    (items, errors)
+}
+
+/// A content line, e.g. `DUE;TZID=Europe/Berlin:20241224T100000`.
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Joins folded lines and splits them into properties, paired with the line
+/// number each one starts on.
+fn unfold(text: &str) -> Vec<(u64, Property)> {
+    let mut lines: Vec<(u64, String)> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some((_, last))) => last.push_str(continuation),
+            _ => lines.push((index as u64 + 1, line.to_string())),
+        }
+    }
+
+    lines
+        .into_iter()
+        .filter_map(|(number, line)| {
+            let (head, value) = line.split_once(':')?;
+            let mut parts = head.split(';');
+            let name = parts.next()?.to_uppercase();
+            let params = parts
+                .filter_map(|param| param.split_once('='))
+                .map(|(key, value)| (key.to_uppercase(), value.trim_matches('"').to_string()))
+                .collect();
+            Some((
+                number,
+                Property {
+                    name,
+                    params,
+                    value: value.to_string(),
+                },
+            ))
+        })
+        .collect()
+}
+
+fn parse_todo(properties: &[Property]) -> std::result::Result<Item, String> {
+    let mut item = Item::new(String::new());
+
+    for property in properties {
+        let value = property.value.as_str();
+        match property.name.as_str() {
+            "UID" => {
+                item.id = value
+                    .strip_suffix(UID_DOMAIN)
+                    .and_then(|id| id.parse().ok())
+                    .unwrap_or(0)
+            }
+            "SUMMARY" => item.name = unescape(value),
+            "DESCRIPTION" => item.description = Some(unescape(value)),
+            "CREATED" => item.created_at = parse_date_time(property)?,
+            "DTSTART" => item.start_date = Some(parse_date_time(property)?),
+            "DUE" => item.deadline = Some(parse_date_time(property)?),
+            "COMPLETED" => {
+                item.completed = true;
+                item.completed_at = Some(parse_date_time(property)?);
+            }
+            "STATUS" => item.completed |= value.eq_ignore_ascii_case("COMPLETED"),
+            "PRIORITY" => {
+                item.weight = match value.trim().parse::<u8>() {
+                    Ok(1..=4) => Weight::High,
+                    Ok(0 | 5) => Weight::Medium,
+                    Ok(6..=9) => Weight::Low,
+                    _ => return Err(format!("invalid PRIORITY: {}", value)),
+                }
+            }
+            "X-TODO-ESTIMATE" => {
+                item.estimate = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid X-TODO-ESTIMATE: {}", value))?,
+                )
+            }
+            _ => {}
+        }
+    }
+
+    if item.name.is_empty() {
+        return Err("missing SUMMARY".to_string());
+    }
+    Ok(item)
+}
+
+fn parse_date_time(property: &Property) -> std::result::Result<DateTime<Local>, String> {
+    let value = property.value.trim();
+    let invalid = || format!("invalid {}: {}", property.name, value);
+
+    if property.param("VALUE") == Some("DATE") || !value.contains('T') {
+        let date = NaiveDate::parse_from_str(value, DATE_FORMAT).map_err(|_| invalid())?;
+        return local(date.and_hms_opt(0, 0, 0).expect("midnight is valid")).ok_or_else(invalid);
+    }
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, DATE_TIME_FORMAT).map_err(|_| invalid())?;
+        return Ok(Utc.from_utc_datetime(&naive).with_timezone(&Local));
+    }
+
+    let naive = NaiveDateTime::parse_from_str(value, DATE_TIME_FORMAT).map_err(|_| invalid())?;
+    match property.param("TZID") {
+        Some(tzid) => {
+            let tz: Tz = tzid
+                .trim_start_matches('/')
+                .parse()
+                .map_err(|_| format!("unknown time zone in {}: {}", property.name, tzid))?;
+            tz.from_local_datetime(&naive)
+                .earliest()
+                .map(|date| date.with_timezone(&Local))
+                .ok_or_else(invalid)
+        }
+        None => local(naive).ok_or_else(invalid),
+    }
+}
+
+fn local(naive: NaiveDateTime) -> Option<DateTime<Local>> {
+    Local.from_local_datetime(&naive).earliest()
+}
+
+fn date_time(date: DateTime<Local>) -> String {
+    format!("{}Z", date.with_timezone(&Utc).format(DATE_TIME_FORMAT))
+}
+
+fn priority(weight: Weight) -> u8 {
+    match weight {
+        Weight::High => 1,
+        Weight::Medium => 5,
+        Weight::Low => 9,
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n' | 'N')) => {
+                result.push('\n');
+                chars.next();
+            }
+            ('\\', Some(escaped @ ('\\' | ';' | ','))) => {
+                result.push(escaped);
+                chars.next();
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// Folds a content line to at most 75 octets per line, ending it with CRLF.
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALARM: &str = include_str!("../../tests/fixtures/ics/alarm.ics");
+
+    #[test]
+    fn ignores_properties_of_nested_components() {
+        let (items, errors) = import(ALARM);
+        assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+
+        let names: Vec<&str> = items.iter().map(|item| item.name.as_str()).collect();
+        assert_eq!(names, ["Pay rent", "Water plants"]);
+        assert_eq!(
+            items[0].description.as_deref(),
+            Some("Transfer to landlord")
+        );
+        assert_eq!(items[0].weight, Weight::High);
+        assert!(items[0].deadline.is_some());
+        assert_eq!(items[1].description, None);
+    }
+}
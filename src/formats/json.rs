@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::Result,
+    repo::{Item, Note},
+};
+
+/// An item with its notes beside its fields.
+#[derive(Serialize)]
+struct ExportRecord<'a> {
+    #[serde(flatten)]
+    item: &'a Item,
+    notes: &'a [Note],
+}
+
+#[derive(Deserialize)]
+struct ImportRecord {
+    #[serde(flatten)]
+    item: Item,
+    /// Missing from exports made before notes were included
+    #[serde(default)]
+    notes: Vec<Note>,
+}
+
+/// Serializes every field of every item, and its notes, so that [`import`]
+/// round-trips them. Tracked time isn't included.
+pub fn export(items: &[(&Item, Vec<Note>)]) -> Result<String> {
+    let records: Vec<ExportRecord> = items
+        .iter()
+        .map(|(item, notes)| ExportRecord { item, notes })
+        .collect();
+    Ok(serde_json::to_string_pretty(&records)?)
+}
+
+pub fn import(text: &str) -> Result<Vec<(Item, Vec<Note>)>> {
+    let records: Vec<ImportRecord> = serde_json::from_str(text)?;
+    Ok(records
+        .into_iter()
+        .map(|record| (record.item, record.notes))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Local;
+
+    use super::*;
+
+    #[test]
+    fn notes_round_trip() {
+        let item = Item::new("task".to_string());
+        let note = Note {
+            body: "a note".to_string(),
+            created_at: Local::now(),
+            edited_at: None,
+        };
+        let text = export(&[(&item, vec![note])]).unwrap();
+
+        let imported = import(&text).unwrap();
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0].0.name, "task");
+        let bodies: Vec<&str> = imported[0]
+            .1
+            .iter()
+            .map(|note| note.body.as_str())
+            .collect();
+        assert_eq!(bodies, ["a note"]);
+    }
+
+    #[test]
+    fn reads_exports_without_notes() {
+        let item = Item::new("task".to_string());
+        let text = serde_json::to_string(&[&item]).unwrap();
+
+        let imported = import(&text).unwrap();
+        assert_eq!(imported[0].0.name, "task");
+        assert!(imported[0].1.is_empty());
+    }
+}
@@ -0,0 +1,164 @@
+//! GitHub-flavored Markdown task lists, e.g.
+//! `- [ ] Write report (high, due 2024-12-24)`.
+//!
+//! The weight, deadline, start date and estimate are written inline in a
+//! trailing parenthesis. On import that parenthesis is only read as fields if
+//! every part of it is one, so `- [ ] Call Bob (re: budget)` keeps its name.
+//! Lines that aren't checklist items, such as headings or prose, are ignored.
+
+use chrono::{DateTime, Local, NaiveTime};
+
+use super::{parse_date, RowError};
+use crate::{
+    cli::{GroupBy, Weight},
+    repo::Item,
+};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Writes one checklist item per item, under a heading per group if
+/// `group_by` is given.
+pub fn export(items: &[Item], group_by: Option<GroupBy>) -> String {
+    let groups: Vec<(String, Vec<&Item>)> = match group_by {
+        None => vec![(String::new(), items.iter().collect())],
+        Some(GroupBy::Weight) => [Weight::High, Weight::Medium, Weight::Low]
+            .into_iter()
+            .map(|weight| {
+                let title = format!("{} weight", capitalize(&weight.to_string()));
+                (title, items.iter().filter(|i| i.weight == weight).collect())
+            })
+            .collect(),
+        Some(GroupBy::Status) => [("Pending", false), ("Completed", true)]
+            .into_iter()
+            .map(|(title, completed)| {
+                let group = items.iter().filter(|i| i.completed == completed).collect();
+                (title.to_string(), group)
+            })
+            .collect(),
+    };
+
+    let mut sections = Vec::new();
+    for (title, group) in groups.into_iter().filter(|(_, group)| !group.is_empty()) {
+        let mut section = String::new();
+        if !title.is_empty() {
+            section.push_str(&format!("## {}\n\n", title));
+        }
+        for item in group {
+            section.push_str(&line(item));
+            section.push('\n');
+        }
+        sections.push(section);
+    }
+
+    sections.join("\n")
+}
+
+/// Reads every `- [ ]` or `- [x]` line, at any indentation and with `-`, `*`
+/// or `+` bullets. Items whose fields fail to parse are reported instead of
+/// aborting.
+pub fn import(text: &str) -> (Vec<Item>, Vec<RowError>) {
+    let mut items = Vec::new();
+    let mut errors = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let Some((completed, rest)) = checkbox(line) else {
+            continue;
+        };
+        match parse_item(completed, rest) {
+            Ok(item) => items.push(item),
+            Err(message) => errors.push(RowError::at_line(index as u64 + 1, message)),
+        }
+    }
+
+    (items, errors)
+}
+
+fn line(item: &Item) -> String {
+    let mut fields = vec![item.weight.to_string()];
+    if let Some(deadline) = item.deadline {
+        fields.push(format!("due {}", date(deadline)));
+    }
+    if let Some(start_date) = item.start_date {
+        fields.push(format!("start {}", date(start_date)));
+    }
+    if let Some(estimate) = item.estimate {
+        fields.push(format!("est {}", estimate));
+    }
+
+    format!(
+        "- [{}] {} ({})",
+        if item.completed { 'x' } else { ' ' },
+        item.name,
+        fields.join(", ")
+    )
+}
+
+/// Splits a checklist line into whether it is checked and the text after
+/// the checkbox.
+fn checkbox(line: &str) -> Option<(bool, &str)> {
+    let rest = line.trim_start().strip_prefix(['-', '*', '+'])?;
+    let rest = rest.strip_prefix(' ')?.trim_start();
+    let (mark, rest) = rest.strip_prefix('[')?.split_once(']')?;
+    let completed = match mark {
+        " " => false,
+        "x" | "X" => true,
+        _ => return None,
+    };
+    Some((completed, rest.trim()))
+}
+
+fn parse_item(completed: bool, text: &str) -> std::result::Result<Item, String> {
+    let mut item = Item::new(text.to_string());
+    item.completed = completed;
+
+    if let Some((name, fields)) = text
+        .strip_suffix(')')
+        .and_then(|text| text.rsplit_once('('))
+        .filter(|(_, fields)| fields.split(',').all(is_field))
+    {
+        item.name = name.trim().to_string();
+        for field in fields.split(',').map(str::trim) {
+            let (key, value) = field.split_once(' ').unwrap_or((field, ""));
+            let invalid = |e| format!("{}: {}", key, e);
+            match key.to_lowercase().as_str() {
+                "due" => item.deadline = Some(parse_date(value.trim()).map_err(invalid)?),
+                "start" => item.start_date = Some(parse_date(value.trim()).map_err(invalid)?),
+                "est" => item.estimate = Some(value.trim().parse().map_err(invalid)?),
+                _ => item.weight = key.parse().map_err(invalid)?,
+            }
+        }
+    }
+
+    if item.name.is_empty() {
+        return Err("missing task text".to_string());
+    }
+    Ok(item)
+}
+
+/// Whether a part of the trailing parenthesis looks like one of our fields.
+fn is_field(field: &str) -> bool {
+    let field = field.trim();
+    let key = field.split(' ').next().unwrap_or_default().to_lowercase();
+    match key.as_str() {
+        "due" | "start" | "est" => field.contains(' '),
+        _ => field.parse::<Weight>().is_ok(),
+    }
+}
+
+/// Formats a date, with the time only if it isn't midnight.
+fn date(date: DateTime<Local>) -> String {
+    if date.time() == NaiveTime::MIN {
+        date.format(DATE_FORMAT).to_string()
+    } else {
+        date.format(DATE_TIME_FORMAT).to_string()
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
@@ -0,0 +1,82 @@
+//! Conversions between todo items and the file formats used by `export` and
+//! `import`.
+
+use std::fmt::Display;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+
+use crate::{
+    error::{Error, Result},
+    repo::{Item, Note},
+};
+
+pub mod csv;
+pub mod ics;
+pub mod json;
+pub mod markdown;
+pub mod org;
+pub mod taskwarrior;
+pub mod todotxt;
+
+/// Date and time formats accepted by imports, tried after RFC 3339.
+const DATE_TIME_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%m/%d/%Y %H:%M",
+    "%d.%m.%Y %H:%M",
+];
+
+/// Date-only formats accepted by imports, read as local midnight.
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d", "%m/%d/%Y", "%d.%m.%Y", "%d %b %Y"];
+
+/// An entry of an imported file that couldn't be turned into an item.
+#[derive(Debug)]
+pub struct RowError {
+    /// Where the entry is in the file, e.g. "line 4"
+    pub location: String,
+    pub message: String,
+}
+
+impl RowError {
+    pub fn at_line(line: u64, message: String) -> Self {
+        RowError {
+            location: format!("line {}", line),
+            message,
+        }
+    }
+}
+
+impl Display for RowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+/// Pairs items from formats that have no notes with an empty set of them, as
+/// [`crate::repo::Repo::import`] expects.
+pub fn without_notes(items: Vec<Item>) -> Vec<(Item, Vec<Note>)> {
+    items.into_iter().map(|item| (item, Vec::new())).collect()
+}
+
+/// Parses a date in RFC 3339 or one of the other common formats, reading
+/// dates without an offset as local time.
+pub fn parse_date(value: &str) -> Result<DateTime<Local>> {
+    let value = value.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date.with_timezone(&Local));
+    }
+
+    DATE_TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            DATE_FORMATS
+                .iter()
+                .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+        .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+        .ok_or_else(|| Error::UnrecognizedDate(value.to_string()))
+}
@@ -0,0 +1,265 @@
+//! Org-mode headlines, e.g.
+//!
+//! ```org
+//! * TODO [#A] Write report
+//!   DEADLINE: <2024-12-24 Tue 17:00> SCHEDULED: <2024-12-20 Fri>
+//!   :PROPERTIES:
+//!   :ID: 3
+//!   :END:
+//!   The description.
+//! ```
+//!
+//! `TODO`/`DONE` map to completion, `[#A]`/`[#B]`/`[#C]` to weights and
+//! `CLOSED:` to the completion time. The property drawer carries the id, the
+//! creation time and the estimate. Headlines without a `TODO` or `DONE`
+//! keyword are ignored on import, at any level.
+
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone};
+
+use super::RowError;
+use crate::{cli::Weight, repo::Item};
+
+const DATE_FORMAT: &str = "%Y-%m-%d %a";
+const DATE_TIME_FORMAT: &str = "%Y-%m-%d %a %H:%M";
+
+pub fn export(items: &[Item]) -> String {
+    items.iter().map(entry).collect()
+}
+
+/// Reads every `TODO` and `DONE` headline. Entries that fail to parse are
+/// reported, by the line of their headline, instead of aborting.
+pub fn import(text: &str) -> (Vec<Item>, Vec<RowError>) {
+    let mut items = Vec::new();
+    let mut errors = Vec::new();
+    let mut entry: Option<(u64, &str, Vec<&str>)> = None;
+
+    let mut finish = |entry: Option<(u64, &str, Vec<&str>)>| {
+        if let Some((line, headline, body)) = entry {
+            match parse_entry(headline, &body) {
+                Ok(item) => items.push(item),
+                Err(message) => errors.push(RowError::at_line(line, message)),
+            }
+        }
+    };
+
+    for (index, line) in text.lines().enumerate() {
+        if let Some(headline) = headline(line) {
+            finish(entry.take());
+            if keyword(headline).is_some() {
+                entry = Some((index as u64 + 1, headline, Vec::new()));
+            }
+        } else if let Some((_, _, body)) = &mut entry {
+            body.push(line);
+        }
+    }
+    finish(entry);
+
+    (items, errors)
+}
+
+fn entry(item: &Item) -> String {
+    let mut lines = vec![format!(
+        "* {} [#{}] {}",
+        if item.completed { "DONE" } else { "TODO" },
+        priority(item.weight),
+        item.name
+    )];
+
+    let mut planning = Vec::new();
+    if let Some(completed_at) = item.completed_at.filter(|_| item.completed) {
+        planning.push(format!("CLOSED: [{}]", date_time(completed_at)));
+    }
+    if let Some(deadline) = item.deadline {
+        planning.push(format!("DEADLINE: <{}>", date(deadline)));
+    }
+    if let Some(start_date) = item.start_date {
+        planning.push(format!("SCHEDULED: <{}>", date(start_date)));
+    }
+    if !planning.is_empty() {
+        lines.push(format!("  {}", planning.join(" ")));
+    }
+
+    lines.push("  :PROPERTIES:".to_string());
+    lines.push(format!("  :ID: {}", item.id));
+    lines.push(format!("  :CREATED: [{}]", date_time(item.created_at)));
+    if let Some(estimate) = item.estimate {
+        lines.push(format!("  :ESTIMATE: {}", estimate));
+    }
+    lines.push("  :END:".to_string());
+
+    if let Some(description) = &item.description {
+        lines.extend(description.lines().map(|line| match line {
+            "" => String::new(),
+            line => format!("  {}", line),
+        }));
+    }
+
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+/// The text after the stars of a headline.
+fn headline(line: &str) -> Option<&str> {
+    let text = line.trim_start_matches('*');
+    (text.len() < line.len())
+        .then(|| text.strip_prefix(' '))
+        .flatten()
+}
+
+/// Whether a headline is done, if it has a `TODO` or `DONE` keyword.
+fn keyword(headline: &str) -> Option<bool> {
+    match headline.split_whitespace().next() {
+        Some("TODO") => Some(false),
+        Some("DONE") => Some(true),
+        _ => None,
+    }
+}
+
+fn parse_entry(headline: &str, body: &[&str]) -> std::result::Result<Item, String> {
+    let mut item = Item::new(String::new());
+    item.completed = keyword(headline).unwrap_or_default();
+
+    let mut title = headline.trim()[4..].trim_start();
+    if let Some((weight, rest)) = title
+        .strip_prefix("[#")
+        .and_then(|rest| rest.split_once(']'))
+    {
+        item.weight = match weight {
+            "A" => Weight::High,
+            "B" => Weight::Medium,
+            "C" => Weight::Low,
+            _ => return Err(format!("invalid priority: [#{}]", weight)),
+        };
+        title = rest.trim_start();
+    }
+    item.name = strip_tags(title).to_string();
+    if item.name.is_empty() {
+        return Err("missing headline text".to_string());
+    }
+
+    let mut lines = body.iter().copied().peekable();
+    if let Some(planning) = lines.next_if(|line| is_planning(line.trim())) {
+        parse_planning(planning.trim(), &mut item)?;
+    }
+    if lines
+        .next_if(|line| line.trim() == ":PROPERTIES:")
+        .is_some()
+    {
+        for line in lines.by_ref().take_while(|line| line.trim() != ":END:") {
+            let Some((key, value)) = line
+                .trim()
+                .strip_prefix(':')
+                .and_then(|line| line.split_once(':'))
+            else {
+                continue;
+            };
+            let value = value.trim();
+            let invalid = || format!("invalid {}: {}", key, value);
+            match key.to_uppercase().as_str() {
+                "ID" => item.id = value.parse().unwrap_or(0),
+                "CREATED" => item.created_at = parse_timestamp(value).ok_or_else(invalid)?,
+                "ESTIMATE" => item.estimate = Some(value.parse().map_err(|_| invalid())?),
+                _ => {}
+            }
+        }
+    }
+
+    let body: Vec<&str> = lines.collect();
+    let indent = body
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let description = body
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let description = description.trim();
+    if !description.is_empty() {
+        item.description = Some(description.to_string());
+    }
+
+    Ok(item)
+}
+
+fn is_planning(line: &str) -> bool {
+    ["CLOSED:", "DEADLINE:", "SCHEDULED:"]
+        .iter()
+        .any(|keyword| line.starts_with(keyword))
+}
+
+/// Reads a line such as `CLOSED: [...] DEADLINE: <...> SCHEDULED: <...>`.
+fn parse_planning(line: &str, item: &mut Item) -> std::result::Result<(), String> {
+    let mut rest = line;
+    while let Some((keyword, after)) = rest.split_once(':') {
+        let after = after.trim_start();
+        let close = match after.chars().next() {
+            Some('<') => '>',
+            Some('[') => ']',
+            _ => return Err(format!("invalid {}: {}", keyword.trim(), after)),
+        };
+        let (timestamp, remaining) = after
+            .split_once(close)
+            .ok_or_else(|| format!("unclosed timestamp after {}", keyword.trim()))?;
+        let date = parse_timestamp(&format!("{}{}", timestamp, close))
+            .ok_or_else(|| format!("invalid {}: {}{}", keyword.trim(), timestamp, close))?;
+
+        match keyword.trim() {
+            "CLOSED" => item.completed_at = Some(date),
+            "DEADLINE" => item.deadline = Some(date),
+            "SCHEDULED" => item.start_date = Some(date),
+            keyword => return Err(format!("unknown planning keyword: {}", keyword)),
+        }
+        rest = remaining;
+    }
+    Ok(())
+}
+
+/// Parses `<2024-12-24 Tue>` or `[2024-12-24 Tue 17:00]`, ignoring weekday
+/// names, repeaters and warning delays.
+fn parse_timestamp(value: &str) -> Option<DateTime<Local>> {
+    let inner = value
+        .trim()
+        .strip_prefix(['<', '['])?
+        .strip_suffix(['>', ']'])?;
+    let mut parts = inner.split_whitespace();
+    let date = NaiveDate::parse_from_str(parts.next()?, "%Y-%m-%d").ok()?;
+    let time = parts
+        .find_map(|part| NaiveTime::parse_from_str(part.get(..5).unwrap_or(part), "%H:%M").ok())
+        .unwrap_or(NaiveTime::MIN);
+    Local.from_local_datetime(&date.and_time(time)).earliest()
+}
+
+/// Drops trailing tags such as `:work:urgent:` from a headline.
+fn strip_tags(title: &str) -> &str {
+    let title = title.trim_end();
+    match title.rsplit_once(' ') {
+        Some((rest, tags)) if tags.len() > 1 && tags.starts_with(':') && tags.ends_with(':') => {
+            rest.trim_end()
+        }
+        _ => title,
+    }
+}
+
+fn priority(weight: Weight) -> char {
+    match weight {
+        Weight::High => 'A',
+        Weight::Medium => 'B',
+        Weight::Low => 'C',
+    }
+}
+
+/// Formats an active timestamp's contents, with the time only if it isn't
+/// midnight.
+fn date(date: DateTime<Local>) -> String {
+    if date.time() == NaiveTime::MIN {
+        date.format(DATE_FORMAT).to_string()
+    } else {
+        date_time(date)
+    }
+}
+
+fn date_time(date: DateTime<Local>) -> String {
+    date.format(DATE_TIME_FORMAT).to_string()
+}
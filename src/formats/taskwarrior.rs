@@ -0,0 +1,241 @@
+//! Taskwarrior's JSON, as read by `task import` and written by `task export`.
+//!
+//! Priorities `H`/`M`/`L` map to weights, `due` to the deadline, `scheduled`
+//! (or else `wait`) to the start date, `entry`/`end` to the creation and
+//! completion times and annotations to notes. Anything else, such as tags or
+//! projects, is counted as unmapped so the caller can report it.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use serde_json::{json, Map, Value};
+
+use super::RowError;
+use crate::{
+    cli::Weight,
+    error::{Error, Result},
+    repo::{Item, Note},
+};
+
+const DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Prefix of the UUIDs given to exported items, followed by their id in hex,
+/// so that importing our own export keeps the ids.
+const UUID_PREFIX: &str = "00000000-0000-4000-8000-";
+
+/// Attributes Taskwarrior derives by itself, which are dropped silently.
+const DERIVED: &[&str] = &["id", "modified", "urgency", "mask", "imask"];
+
+/// Attributes that map onto item fields.
+const MAPPED: &[&str] = &[
+    "uuid",
+    "description",
+    "status",
+    "entry",
+    "end",
+    "due",
+    "scheduled",
+    "wait",
+    "priority",
+    "annotations",
+    "estimate",
+];
+
+pub struct Import {
+    pub items: Vec<(Item, Vec<Note>)>,
+    pub errors: Vec<RowError>,
+    /// Attributes with no equivalent here, with how many tasks had each
+    pub unmapped: BTreeMap<String, usize>,
+}
+
+/// Writes items as Taskwarrior tasks. Descriptions become an annotation and
+/// estimates an `estimate` UDA; how often that happened is returned alongside.
+pub fn export(items: &[(&Item, Vec<Note>)]) -> Result<(String, BTreeMap<String, usize>)> {
+    let mut approximated = BTreeMap::new();
+
+    let tasks: Vec<Value> = items
+        .iter()
+        .map(|(item, notes)| {
+            let mut task = Map::new();
+            task.insert(
+                "uuid".to_string(),
+                json!(format!("{}{:012x}", UUID_PREFIX, item.id)),
+            );
+            task.insert("description".to_string(), json!(item.name));
+            task.insert(
+                "status".to_string(),
+                json!(if item.completed {
+                    "completed"
+                } else {
+                    "pending"
+                }),
+            );
+            task.insert("entry".to_string(), json!(date(item.created_at)));
+            task.insert("priority".to_string(), json!(priority(item.weight)));
+            if let Some(completed_at) = item.completed_at {
+                task.insert("end".to_string(), json!(date(completed_at)));
+            }
+            if let Some(deadline) = item.deadline {
+                task.insert("due".to_string(), json!(date(deadline)));
+            }
+            if let Some(start_date) = item.start_date {
+                task.insert("scheduled".to_string(), json!(date(start_date)));
+            }
+            if let Some(estimate) = item.estimate {
+                task.insert("estimate".to_string(), json!(estimate.to_string()));
+                *approximated
+                    .entry("estimate (as an `estimate` UDA)".to_string())
+                    .or_insert(0) += 1;
+            }
+
+            let mut annotations: Vec<Value> = Vec::new();
+            if let Some(description) = &item.description {
+                annotations.push(json!({
+                    "entry": date(item.created_at),
+                    "description": description,
+                }));
+                *approximated
+                    .entry("description (as an annotation)".to_string())
+                    .or_insert(0) += 1;
+            }
+            annotations.extend(notes.iter().map(|note| {
+                json!({
+                    "entry": date(note.created_at),
+                    "description": note.body,
+                })
+            }));
+            if !annotations.is_empty() {
+                task.insert("annotations".to_string(), Value::Array(annotations));
+            }
+
+            Value::Object(task)
+        })
+        .collect();
+
+    Ok((serde_json::to_string_pretty(&tasks)?, approximated))
+}
+
+/// Reads the output of `task export`. Deleted and recurring template tasks
+/// are skipped and counted as unmapped; tasks that fail to parse are reported
+/// by their position in the array.
+pub fn import(text: &str) -> Result<Import> {
+    let tasks: Vec<Value> = serde_json::from_str(text)?;
+    let mut import = Import {
+        items: Vec::new(),
+        errors: Vec::new(),
+        unmapped: BTreeMap::new(),
+    };
+
+    for (index, task) in tasks.iter().enumerate() {
+        let result = task
+            .as_object()
+            .ok_or_else(|| "not a JSON object".to_string())
+            .and_then(|task| parse_task(task, &mut import.unmapped));
+
+        match result {
+            Ok(Some(item)) => import.items.push(item),
+            Ok(None) => {}
+            Err(message) => import.errors.push(RowError {
+                location: format!("task {}", index + 1),
+                message,
+            }),
+        }
+    }
+
+    Ok(import)
+}
+
+fn parse_task(
+    task: &Map<String, Value>,
+    unmapped: &mut BTreeMap<String, usize>,
+) -> std::result::Result<Option<(Item, Vec<Note>)>, String> {
+    let text = |key: &str| task.get(key).and_then(Value::as_str);
+    let date_of = |key: &str| {
+        text(key)
+            .map(|value| parse_date(value).ok_or_else(|| format!("invalid {}: {}", key, value)))
+            .transpose()
+    };
+
+    let status = text("status").unwrap_or("pending");
+    if matches!(status, "deleted" | "recurring") {
+        *unmapped.entry(format!("status: {}", status)).or_insert(0) += 1;
+        return Ok(None);
+    }
+
+    let name = text("description")
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| "missing description".to_string())?;
+    let mut item = Item::new(name.to_string());
+
+    if let Some(id) = text("uuid")
+        .and_then(|uuid| uuid.strip_prefix(UUID_PREFIX))
+        .and_then(|id| i64::from_str_radix(id, 16).ok())
+    {
+        item.id = id;
+    }
+    item.completed = status == "completed";
+    if let Some(created_at) = date_of("entry")? {
+        item.created_at = created_at;
+    }
+    item.completed_at = date_of("end")?.filter(|_| item.completed);
+    item.deadline = date_of("due")?;
+    item.start_date = date_of("scheduled")?.or(date_of("wait")?);
+    item.weight = match text("priority") {
+        Some("H") => Weight::High,
+        Some("M") | None => Weight::Medium,
+        Some("L") => Weight::Low,
+        Some(priority) => return Err(format!("invalid priority: {}", priority)),
+    };
+    item.estimate = text("estimate")
+        .map(|value| value.parse().map_err(|e: Error| format!("estimate: {}", e)))
+        .transpose()?;
+
+    let notes = task
+        .get("annotations")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|annotation| {
+            let body = annotation
+                .get("description")
+                .and_then(Value::as_str)
+                .ok_or_else(|| "annotation without description".to_string())?;
+            let created_at = annotation
+                .get("entry")
+                .and_then(Value::as_str)
+                .and_then(parse_date)
+                .unwrap_or(item.created_at);
+            Ok(Note {
+                body: body.to_string(),
+                created_at,
+                edited_at: None,
+            })
+        })
+        .collect::<std::result::Result<Vec<_>, String>>()?;
+
+    for key in task.keys() {
+        let is_empty_list = task[key].as_array().is_some_and(Vec::is_empty);
+        if !MAPPED.contains(&key.as_str()) && !DERIVED.contains(&key.as_str()) && !is_empty_list {
+            *unmapped.entry(key.clone()).or_insert(0) += 1;
+        }
+    }
+
+    Ok(Some((item, notes)))
+}
+
+fn priority(weight: Weight) -> &'static str {
+    match weight {
+        Weight::High => "H",
+        Weight::Medium => "M",
+        Weight::Low => "L",
+    }
+}
+
+fn date(date: DateTime<Local>) -> String {
+    date.with_timezone(&Utc).format(DATE_FORMAT).to_string()
+}
+
+fn parse_date(value: &str) -> Option<DateTime<Local>> {
+    let naive = NaiveDateTime::parse_from_str(value, DATE_FORMAT).ok()?;
+    Some(Utc.from_utc_datetime(&naive).with_timezone(&Local))
+}
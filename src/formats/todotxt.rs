@@ -0,0 +1,218 @@
+//! The [todo.txt](https://github.com/todotxt/todo.txt) format: one task per
+//! line, e.g. `x 2024-12-25 2024-12-20 Buy gifts +family due:2024-12-24`.
+//!
+//! Weights map to the priorities `(A)`, `(B)` and `(C)`; completed tasks carry
+//! theirs as `pri:` since todo.txt drops the priority on completion. `due:`,
+//! `t:` and `est:` hold the deadline, start date and estimate. Projects and
+//! contexts stay part of the task name.
+
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+
+use super::RowError;
+use crate::{cli::Weight, repo::Item};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+pub fn export(items: &[Item]) -> String {
+    items
+        .iter()
+        .map(|item| format!("{}\n", line(item)))
+        .collect()
+}
+
+/// Reads one item per non-blank line. Lines that fail to parse are reported
+/// instead of aborting.
+pub fn import(text: &str) -> (Vec<Item>, Vec<RowError>) {
+    let mut items = Vec::new();
+    let mut errors = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_line(line) {
+            Ok(item) => items.push(item),
+            Err(message) => errors.push(RowError::at_line(index as u64 + 1, message)),
+        }
+    }
+
+    (items, errors)
+}
+
+fn line(item: &Item) -> String {
+    let mut words = Vec::new();
+
+    if item.completed {
+        words.push("x".to_string());
+        words.push(date(item.completed_at.unwrap_or(item.created_at)));
+    } else {
+        words.push(format!("({})", priority(item.weight)));
+    }
+    words.push(date(item.created_at));
+    words.push(item.name.clone());
+    if item.completed {
+        words.push(format!("pri:{}", priority(item.weight)));
+    }
+    if let Some(deadline) = item.deadline {
+        words.push(format!("due:{}", date(deadline)));
+    }
+    if let Some(start_date) = item.start_date {
+        words.push(format!("t:{}", date(start_date)));
+    }
+    if let Some(estimate) = item.estimate {
+        words.push(format!("est:{}", estimate.to_string().replace(' ', "")));
+    }
+
+    words.join(" ")
+}
+
+fn parse_line(line: &str) -> std::result::Result<Item, String> {
+    let mut item = Item::new(String::new());
+    let mut words = line.split_whitespace().peekable();
+
+    if words.next_if_eq(&"x").is_some() {
+        item.completed = true;
+        item.completed_at = words
+            .next_if(|word| parse_date(word).is_some())
+            .and_then(parse_date);
+    }
+    if let Some(weight) = words.peek().and_then(|word| parse_priority(word)) {
+        item.weight = weight;
+        words.next();
+    }
+    if let Some(created_at) = words
+        .next_if(|word| parse_date(word).is_some())
+        .and_then(parse_date)
+    {
+        item.created_at = created_at;
+    }
+
+    let mut name = Vec::new();
+    for word in words {
+        let invalid = || format!("invalid {}", word);
+        match word.split_once(':') {
+            Some(("due", value)) => item.deadline = Some(parse_date(value).ok_or_else(invalid)?),
+            Some(("t", value)) => item.start_date = Some(parse_date(value).ok_or_else(invalid)?),
+            Some(("pri", value)) => {
+                item.weight = parse_priority(&format!("({})", value)).ok_or_else(invalid)?
+            }
+            Some(("est", value)) => item.estimate = Some(value.parse().map_err(|_| invalid())?),
+            _ => name.push(word),
+        }
+    }
+
+    item.name = name.join(" ");
+    if item.name.is_empty() {
+        return Err("missing task text".to_string());
+    }
+    Ok(item)
+}
+
+fn priority(weight: Weight) -> char {
+    match weight {
+        Weight::High => 'A',
+        Weight::Medium => 'B',
+        Weight::Low => 'C',
+    }
+}
+
+/// Parses `(A)` to `(Z)`, treating every priority below `(B)` as low.
+fn parse_priority(word: &str) -> Option<Weight> {
+    match word
+        .strip_prefix('(')?
+        .strip_suffix(')')?
+        .chars()
+        .collect::<Vec<_>>()[..]
+    {
+        ['A'] => Some(Weight::High),
+        ['B'] => Some(Weight::Medium),
+        [c] if c.is_ascii_uppercase() => Some(Weight::Low),
+        _ => None,
+    }
+}
+
+fn date(date: DateTime<Local>) -> String {
+    date.format(DATE_FORMAT).to_string()
+}
+
+fn parse_date(word: &str) -> Option<DateTime<Local>> {
+    let date = NaiveDate::parse_from_str(word, DATE_FORMAT).ok()?;
+    Local
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+        .earliest()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Estimate;
+
+    const CANONICAL: &str = include_str!("../../tests/fixtures/todotxt/canonical.txt");
+    const LOOSE: &str = include_str!("../../tests/fixtures/todotxt/loose.txt");
+    const LOOSE_EXPECTED: &str = include_str!("../../tests/fixtures/todotxt/loose.expected.txt");
+
+    fn import_ok(text: &str) -> Vec<Item> {
+        let (items, errors) = import(text);
+        assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+        items
+    }
+
+    #[test]
+    fn canonical_file_round_trips() {
+        assert_eq!(export(&import_ok(CANONICAL)), CANONICAL);
+    }
+
+    #[test]
+    fn loose_file_exports_canonically() {
+        assert_eq!(export(&import_ok(LOOSE)), LOOSE_EXPECTED);
+    }
+
+    #[test]
+    fn reads_fields() {
+        let items = import_ok(CANONICAL);
+        let day = |s: &str| parse_date(s).unwrap();
+
+        let weights: Vec<Weight> = items.iter().map(|item| item.weight).collect();
+        assert_eq!(
+            weights,
+            [
+                Weight::High,
+                Weight::Medium,
+                Weight::Low,
+                Weight::High,
+                Weight::Low
+            ]
+        );
+
+        let report = &items[0];
+        assert_eq!(report.name, "Write report");
+        assert!(!report.completed);
+        assert_eq!(report.created_at, day("2024-12-20"));
+        assert_eq!(report.deadline, Some(day("2024-12-24")));
+        assert_eq!(report.start_date, Some(day("2024-12-21")));
+        assert_eq!(
+            report.estimate,
+            Some(Estimate::Time(chrono::Duration::minutes(150)))
+        );
+
+        assert_eq!(items[1].name, "Buy milk +home @shop");
+        assert_eq!(items[2].estimate, Some(Estimate::Points(5)));
+
+        let invoice = &items[3];
+        assert!(invoice.completed);
+        assert_eq!(invoice.completed_at, Some(day("2024-12-23")));
+        assert_eq!(invoice.created_at, day("2024-12-20"));
+        assert_eq!(invoice.deadline, Some(day("2024-12-22")));
+    }
+
+    #[test]
+    fn reports_bad_lines_and_keeps_the_rest() {
+        let (items, errors) = import("(A) Fine\n\n(B) Bad due:tomorrow\nx 2024-12-23\n");
+        assert_eq!(items.len(), 1);
+        let lines: Vec<_> = errors.iter().map(|error| error.to_string()).collect();
+        assert_eq!(
+            lines,
+            ["line 3: invalid due:tomorrow", "line 4: missing task text"]
+        );
+    }
+}
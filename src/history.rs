@@ -0,0 +1,38 @@
+//! The REPL's command history, kept in `~/.todo/history.txt` so it carries
+//! over between sessions.
+
+use std::path::PathBuf;
+
+use reedline::{FileBackedHistory, History, SearchQuery};
+
+use crate::error::{Error, Result};
+
+pub fn path() -> Result<PathBuf> {
+    let home_dir = dirs::home_dir().ok_or(Error::HomeDirNotFound)?;
+    Ok(home_dir.join(".todo").join("history.txt"))
+}
+
+/// Opens the history file, keeping at most `size` commands. Repeating the
+/// previous command doesn't add it again.
+pub fn open(size: usize) -> Result<FileBackedHistory> {
+    let path = path()?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    Ok(FileBackedHistory::with_file(size, path)?)
+}
+
+/// The distinct commands containing `term`, oldest first, each where it was
+/// last run.
+pub fn search(history: &dyn History, term: &str) -> Result<Vec<String>> {
+    let newest_first = history.search(SearchQuery::all_that_contain_rev(term.to_string()))?;
+
+    let mut commands: Vec<String> = Vec::new();
+    for item in newest_first {
+        if !commands.contains(&item.command_line) {
+            commands.push(item.command_line);
+        }
+    }
+    commands.reverse();
+    Ok(commands)
+}
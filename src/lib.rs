@@ -1,4 +1,16 @@
+pub mod board;
+pub mod calendar;
+pub mod chart;
 pub mod cli;
+pub mod completer;
+pub mod config;
+pub mod duration;
+pub mod editor;
 pub mod error;
+pub mod formats;
+pub mod history;
 pub mod prompt;
 pub mod repo;
+pub mod stats;
+pub mod syntax;
+pub mod tui;
@@ -1,13 +1,200 @@
+use std::{collections::BTreeMap, fs};
+
+use chrono::{Datelike, Duration, Local};
 use clap::Parser;
+use crossterm::terminal;
 use nu_ansi_term::{Color, Style};
-use reedline::{DefaultHinter, Reedline, Signal};
+use reedline::{
+    default_emacs_keybindings, ColumnarMenu, DefaultHinter, Emacs, KeyCode, KeyModifiers,
+    MenuBuilder, Reedline, ReedlineEvent, ReedlineMenu, Signal,
+};
 use todo::{
-    cli::{Args, Command},
+    board::{self, Column},
+    calendar::{self, weight_color, Event},
+    chart,
+    cli::{
+        Args, ChartKind, Command, Estimate, FileFormat, OutputFormat, Period, Quadrant, SortKey,
+        Weight,
+    },
+    completer::{self, TodoCompleter},
+    config::Config,
+    duration, editor,
     error::Result,
+    formats, history,
     prompt::TodoPrompt,
-    repo::Repo,
+    repo::{self, BadRow, Draft, Item, Repo, SortBy},
+    stats::{self, Stats},
+    syntax::{TodoHighlighter, TodoValidator},
+    tui,
 };
 
+/// Warns about rows `Repo::list` or `Repo::time_entries` had to skip and how
+/// to fix them.
+fn warn_bad_rows(bad_rows: &[BadRow]) {
+    for row in bad_rows {
+        eprintln!("warning: skipped {}", row);
+    }
+    if !bad_rows.is_empty() {
+        eprintln!("  See them with `todo doctor` and fix them with `todo doctor --repair`");
+    }
+}
+
+fn print_details(item: &Item, tracked: Duration) {
+    let status = if item.completed { "✓" } else { " " };
+
+    println!("[{}] {}", status, item.name);
+    println!("    ID: {}", item.id);
+    println!("    Weight: {}", item.weight);
+    println!(
+        "    Status: {}",
+        if item.completed {
+            "completed"
+        } else {
+            "pending"
+        }
+    );
+    if let Some(desc) = &item.description {
+        println!("    Description: {}", desc);
+    }
+    println!("    Start: {}", Item::format_date(item.start_date));
+    println!("    Deadline: {}", Item::format_date(item.deadline));
+    if let Some(estimate) = item.estimate {
+        println!("    Estimate: {}", estimate);
+    }
+    println!("    Created: {}", Item::format_date(Some(item.created_at)));
+    println!("    Completed: {}", Item::format_date(item.completed_at));
+    println!("    Age: {}", duration::format(item.age()));
+    if let Some(remaining) = item.time_until_deadline() {
+        if remaining < Duration::zero() {
+            println!("    Overdue by: {}", duration::format(remaining));
+        } else {
+            println!("    Due in: {}", duration::format(remaining));
+        }
+    }
+    if let Some(taken) = item.time_to_completion() {
+        println!("    Time to completion: {}", duration::format(taken));
+    }
+    if tracked > Duration::zero() {
+        println!("    Tracked: {}", duration::format(tracked));
+    }
+}
+
+/// Sums the estimates of the pending items as (minutes, story points).
+fn remaining_estimate(items: &[Item]) -> (i64, u32) {
+    items
+        .iter()
+        .filter(|item| !item.completed)
+        .fold((0, 0), |(minutes, points), item| match item.estimate {
+            Some(Estimate::Time(duration)) => {
+                (minutes.saturating_add(duration.num_minutes()), points)
+            }
+            Some(Estimate::Points(estimate)) => (minutes, points.saturating_add(estimate)),
+            None => (minutes, points),
+        })
+}
+
+fn format_estimate_total(minutes: i64, points: u32) -> String {
+    let mut parts = Vec::new();
+    if minutes > 0 {
+        let total = Duration::try_minutes(minutes).unwrap_or(Duration::MAX);
+        parts.push(duration::format_exact(total));
+    }
+    if points > 0 {
+        parts.push(format!("{}pt", points));
+    }
+    parts.join(" + ")
+}
+
+fn format_ratio(actual: Duration, estimate: Duration) -> String {
+    if estimate.num_minutes() == 0 {
+        return String::new();
+    }
+    format!(
+        "({:.0}% of estimate)",
+        actual.num_minutes() as f64 / estimate.num_minutes() as f64 * 100.0
+    )
+}
+
+fn print_stats(stats: &Stats) {
+    match (stats.since, stats.until) {
+        (None, None) => println!("Stats (all time)"),
+        (since, until) => println!(
+            "Stats ({} to {})",
+            since.map_or("start".to_string(), |d| d.to_string()),
+            until.map_or("today".to_string(), |d| d.to_string())
+        ),
+    }
+
+    println!();
+    println!("Created vs completed per week");
+    if stats.weeks.is_empty() {
+        println!("  No activity");
+    }
+    for (week, counts) in &stats.weeks {
+        println!(
+            "  {}  {:>3} created  {:>3} completed",
+            week, counts.created, counts.completed
+        );
+    }
+
+    println!();
+    println!("Average time to completion");
+    if stats.completion_time.is_empty() {
+        println!("  No completed tasks");
+    }
+    for (weight, time) in stats.completion_time.iter().rev() {
+        println!(
+            "  {:<8} {} ({} tasks)",
+            weight.to_string(),
+            duration::format(Duration::seconds(time.average_seconds)),
+            time.completed
+        );
+    }
+
+    println!();
+    println!("Deadlines");
+    match stats.deadlines.on_time_rate {
+        Some(rate) => println!(
+            "  {} on time, {} late ({:.0}% on time)",
+            stats.deadlines.on_time,
+            stats.deadlines.late,
+            rate * 100.0
+        ),
+        None => println!("  No completed tasks with deadlines"),
+    }
+
+    println!();
+    println!("Open backlog");
+    let open: usize = stats.backlog.values().sum();
+    for (weight, count) in stats.backlog.iter().rev() {
+        println!("  {:<8} {}", weight.to_string(), count);
+    }
+    println!("  {:<8} {}", "total", open);
+
+    println!();
+    println!("Completion streaks");
+    println!("  Current: {} days", stats.streaks.current_days);
+    match stats.streaks.longest_ended {
+        Some(ended) => println!(
+            "  Longest: {} days (ended {})",
+            stats.streaks.longest_days, ended
+        ),
+        None => println!("  Longest: 0 days"),
+    }
+}
+
+fn print_draft(draft: &Draft) {
+    if let Some(desc) = &draft.description {
+        println!("  Description: {}", desc);
+    }
+    println!("  Weight: {}", draft.weight);
+    println!("  Start: {}", Item::format_date(draft.start_date));
+    println!("  Deadline: {}", Item::format_date(draft.deadline));
+    if let Some(estimate) = draft.estimate {
+        println!("  Estimate: {}", estimate);
+    }
+}
+
 fn execute_command(repo: &mut Repo, command: Command) -> Result<()> {
     match command {
         Command::Add {
@@ -16,13 +203,36 @@ fn execute_command(repo: &mut Repo, command: Command) -> Result<()> {
             weight,
             days_to_start,
             days_to_complete,
+            estimate,
+            editor,
+            full,
         } => {
+            if editor {
+                let mut draft = Draft {
+                    name: name.unwrap_or_default(),
+                    description,
+                    weight: weight.unwrap_or(Weight::Medium),
+                    start_date: days_to_start.map(repo::days_from_now),
+                    deadline: days_to_complete.map(repo::days_from_now),
+                    estimate,
+                };
+                if !editor::edit_draft(&mut draft, full, |draft| repo.insert(draft))? {
+                    println!("Aborted: no changes made");
+                    return Ok(());
+                }
+                println!("✓ Added new task: {}", draft.name);
+                print_draft(&draft);
+                return Ok(());
+            }
+
+            let name = name.expect("clap requires a name without --full");
             repo.add(
                 name.clone(),
                 description.clone(),
                 weight,
                 days_to_start,
                 days_to_complete,
+                estimate,
             )?;
             println!("✓ Added new task: {}", name);
             if let Some(desc) = description {
@@ -37,6 +247,9 @@ fn execute_command(repo: &mut Repo, command: Command) -> Result<()> {
             if let Some(complete) = days_to_complete {
                 println!("  Complete in: {} days", complete);
             }
+            if let Some(estimate) = estimate {
+                println!("  Estimate: {}", estimate);
+            }
         }
         Command::Remove { name } => {
             repo.remove(&name)?;
@@ -49,7 +262,43 @@ fn execute_command(repo: &mut Repo, command: Command) -> Result<()> {
             weight,
             days_to_start,
             days_to_complete,
+            estimate,
+            editor,
+            full,
         } => {
+            if editor {
+                let mut draft = repo.get(&name)?.draft();
+                let name = draft.name.clone();
+                if let Some(new_name) = new_name {
+                    draft.name = new_name;
+                }
+                if description.is_some() {
+                    draft.description = description;
+                }
+                if let Some(weight) = weight {
+                    draft.weight = weight;
+                }
+                if let Some(days) = days_to_start {
+                    draft.start_date = Some(repo::days_from_now(days));
+                }
+                if let Some(days) = days_to_complete {
+                    draft.deadline = Some(repo::days_from_now(days));
+                }
+                if estimate.is_some() {
+                    draft.estimate = estimate;
+                }
+                if !editor::edit_draft(&mut draft, full, |draft| repo.update(&name, draft))? {
+                    println!("Aborted: no changes made");
+                    return Ok(());
+                }
+                println!("✓ Updated task: {}", name);
+                if draft.name != name {
+                    println!("  New name: {}", draft.name);
+                }
+                print_draft(&draft);
+                return Ok(());
+            }
+
             repo.edit(
                 name.clone(),
                 new_name.clone(),
@@ -57,6 +306,7 @@ fn execute_command(repo: &mut Repo, command: Command) -> Result<()> {
                 weight,
                 days_to_start,
                 days_to_complete,
+                estimate,
             )?;
             println!("✓ Updated task: {}", name);
             if let Some(new) = new_name {
@@ -74,18 +324,627 @@ fn execute_command(repo: &mut Repo, command: Command) -> Result<()> {
             if let Some(complete) = days_to_complete {
                 println!("  New complete in: {} days", complete);
             }
+            if let Some(estimate) = estimate {
+                println!("  New estimate: {}", estimate);
+            }
         }
         Command::Complete { name } => {
             repo.complete(&name)?;
             println!("✓ Marked as complete: {}", name);
         }
+        Command::Note {
+            name,
+            text,
+            edit,
+            delete,
+        } => match (text, edit, delete) {
+            (_, _, Some(index)) => {
+                repo.remove_note(&name, index)?;
+                println!("✓ Deleted note {} from: {}", index, name);
+            }
+            (Some(text), Some(index), None) => {
+                repo.edit_note(&name, index, text)?;
+                println!("✓ Updated note {} on: {}", index, name);
+            }
+            (Some(text), None, None) => {
+                let index = repo.add_note(&name, text)?;
+                println!("✓ Added note {} to: {}", index, name);
+            }
+            (None, _, None) => unreachable!("clap requires text unless deleting"),
+        },
+        Command::Show { name, format } => {
+            let item = repo.get(&name)?;
+            let notes = repo.notes(&item.name)?;
+            let (mut tracked, bad_entries) = repo.tracked()?;
+            warn_bad_rows(&bad_entries);
+            let tracked = tracked.remove(&item.id).unwrap_or_else(Duration::zero);
+
+            match format {
+                OutputFormat::Json => {
+                    let mut value = serde_json::to_value(&item)?;
+                    value["age_seconds"] = item.age().num_seconds().into();
+                    value["seconds_until_deadline"] =
+                        item.time_until_deadline().map(|d| d.num_seconds()).into();
+                    value["seconds_to_completion"] =
+                        item.time_to_completion().map(|d| d.num_seconds()).into();
+                    value["tracked_seconds"] = tracked.num_seconds().into();
+                    value["notes"] = serde_json::to_value(&notes)?;
+                    println!("{}", serde_json::to_string_pretty(&value)?);
+                }
+                OutputFormat::Text => {
+                    print_details(&item, tracked);
+                    println!();
+                    if notes.is_empty() {
+                        println!("No notes");
+                    } else {
+                        println!("Notes");
+                        for (index, note) in notes.iter().enumerate() {
+                            println!("  {:>2}. {}", index + 1, note);
+                        }
+                    }
+                }
+            }
+        }
+        Command::Start { name } => {
+            let entry = repo.start_timer(&name)?;
+            println!(
+                "✓ Started timer on: {} at {}",
+                entry.name,
+                entry.started_at.format("%H:%M")
+            );
+        }
+        Command::Stop => {
+            let entry = repo.stop_timer()?;
+            println!(
+                "✓ Stopped timer on: {} after {}",
+                entry.name,
+                duration::format(entry.duration())
+            );
+        }
+        Command::LogTime { name, duration } => {
+            let entry = repo.log_time(&name, duration)?;
+            println!(
+                "✓ Logged {} on: {}",
+                duration::format(entry.duration()),
+                entry.name
+            );
+        }
+        Command::Timesheet { by, since, until } => {
+            let (entries, bad_entries) = repo.time_entries()?;
+            warn_bad_rows(&bad_entries);
+            let entries: Vec<_> = entries
+                .into_iter()
+                .filter(|entry| {
+                    let date = entry.started_at.date_naive();
+                    since.is_none_or(|since| date >= since)
+                        && until.is_none_or(|until| date <= until)
+                })
+                .collect();
+            if entries.is_empty() {
+                println!("No time tracked");
+                return Ok(());
+            }
+
+            let mut periods: BTreeMap<String, BTreeMap<String, Duration>> = BTreeMap::new();
+            for entry in &entries {
+                let period = match by {
+                    Period::Day => entry.started_at.format("%Y-%m-%d (%a)").to_string(),
+                    Period::Week => entry.started_at.format("%G-W%V").to_string(),
+                };
+                *periods
+                    .entry(period)
+                    .or_default()
+                    .entry(entry.name.clone())
+                    .or_insert_with(Duration::zero) += entry.duration();
+            }
+
+            println!("Timesheet");
+            println!();
+            let mut total = Duration::zero();
+            for (period, tasks) in periods {
+                let subtotal = tasks.values().fold(Duration::zero(), |acc, d| acc + *d);
+                total += subtotal;
+                println!("{}  {}", period, duration::format(subtotal));
+                for (name, tracked) in tasks {
+                    println!("    {:<30} {}", name, duration::format(tracked));
+                }
+                println!();
+            }
+            println!("Total: {}", duration::format(total));
+        }
+        Command::Estimates => {
+            let (tracked, bad_entries) = repo.tracked()?;
+            let (items, mut bad_rows) = repo.list(None, true, SortBy::Created, None, None)?;
+            bad_rows.extend(bad_entries);
+            warn_bad_rows(&bad_rows);
+            let items: Vec<_> = items
+                .into_iter()
+                .filter(|item| item.estimate.is_some())
+                .collect();
+            if items.is_empty() {
+                println!("No completed tasks with estimates");
+                return Ok(());
+            }
+
+            println!("Estimates vs actual (completed tasks)");
+            println!();
+
+            let (mut estimated, mut actual) = (Duration::zero(), Duration::zero());
+            let (mut points, mut point_actual) = (0, Duration::zero());
+            for item in &items {
+                let (spent, source) = match tracked.get(&item.id) {
+                    Some(tracked) => (*tracked, "tracked"),
+                    None => (item.time_spent().unwrap_or_else(Duration::zero), "elapsed"),
+                };
+                let comparison = match item.estimate {
+                    Some(Estimate::Time(estimate)) => {
+                        estimated = duration::saturating_add(estimated, estimate);
+                        actual = duration::saturating_add(actual, spent);
+                        format_ratio(spent, estimate)
+                    }
+                    Some(Estimate::Points(estimate)) => {
+                        points = u32::saturating_add(points, estimate);
+                        point_actual = duration::saturating_add(point_actual, spent);
+                        String::new()
+                    }
+                    None => unreachable!("items without estimates are filtered out"),
+                };
+                let line = format!(
+                    "  {:<30} est {:<10} actual {:<10} ({}) {}",
+                    item.name,
+                    item.estimate.map(|e| e.to_string()).unwrap_or_default(),
+                    duration::format(spent),
+                    source,
+                    comparison
+                );
+                println!("{}", line.trim_end());
+            }
+
+            println!();
+            if estimated > Duration::zero() {
+                println!(
+                    "Time estimates: {} estimated, {} actual {}",
+                    duration::format(estimated),
+                    duration::format(actual),
+                    format_ratio(actual, estimated)
+                );
+            }
+            if points > 0 {
+                println!(
+                    "Point estimates: {}pt took {} ({} per point)",
+                    points,
+                    duration::format(point_actual),
+                    duration::format(point_actual / i32::try_from(points).unwrap_or(i32::MAX))
+                );
+            }
+        }
+        Command::Board { weight, done_limit } => {
+            let config = Config::load()?;
+            let workflow = &config.workflow;
+            let (items, bad_rows) = repo.list(weight, false, SortBy::Deadline, None, None)?;
+            warn_bad_rows(&bad_rows);
+
+            let mut columns: Vec<Column> = workflow
+                .statuses
+                .iter()
+                .map(|status| Column {
+                    status,
+                    items: Vec::new(),
+                    hidden: 0,
+                })
+                .collect();
+            for item in &items {
+                let status = workflow.status_of(item);
+                if let Some(column) = columns.iter_mut().find(|c| c.status == status) {
+                    column.items.push(item);
+                }
+            }
+            if let Some(done) = columns.last_mut() {
+                done.items
+                    .sort_by_key(|item| std::cmp::Reverse(item.completed_at));
+                done.hidden = done.items.len().saturating_sub(done_limit);
+                done.items.truncate(done_limit);
+            }
+
+            let (width, _) = terminal::size().unwrap_or((80, 24));
+            println!("{}", board::render(&columns, width as usize));
+        }
+        Command::Move { name, status } => {
+            let config = Config::load()?;
+            let status = config.workflow.find(&status)?;
+            let done = status == config.workflow.done();
+            let item = repo.set_status(&name, status, done)?;
+            println!("✓ Moved {} to: {}", item.name, status);
+        }
+        Command::Next => {
+            let config = Config::load()?;
+            let (items, bad_rows) = repo.list(None, false, SortBy::Created, None, None)?;
+            warn_bad_rows(&bad_rows);
+
+            let now = Local::now();
+            let next = items
+                .iter()
+                .filter(|item| !item.completed)
+                .filter(|item| item.start_date.is_none_or(|start| start <= now))
+                .map(|item| (item, config.urgency.score(item, &config.workflow, now)))
+                .max_by(|(_, a), (_, b)| a.total.total_cmp(&b.total));
+            let Some((item, score)) = next else {
+                println!("Nothing to do right now");
+                return Ok(());
+            };
+
+            let (mut tracked, bad_entries) = repo.tracked()?;
+            warn_bad_rows(&bad_entries);
+            let tracked = tracked.remove(&item.id).unwrap_or_else(Duration::zero);
+            print_details(item, tracked);
+            println!();
+            println!("Urgency: {:.1}", score.total);
+            for (term, value) in &score.terms {
+                println!("  {:>6.2}  {}", value, term);
+            }
+        }
+        Command::Tui => tui::run(repo)?,
+        Command::History { search } => {
+            let history = history::open(Config::load()?.history.size)?;
+            let commands = history::search(&history, search.as_deref().unwrap_or(""))?;
+            for (index, command) in commands.iter().enumerate() {
+                println!("{:>4}  {}", index + 1, command);
+            }
+        }
+        Command::Matrix => {
+            let matrix = Config::load()?.matrix;
+            let (items, bad_rows) = repo.list(None, false, SortBy::Deadline, None, None)?;
+            warn_bad_rows(&bad_rows);
+
+            let now = Local::now();
+            let quadrant = |quadrant, title| Column {
+                status: title,
+                items: items
+                    .iter()
+                    .filter(|item| matrix.quadrant(item, now) == Some(quadrant))
+                    .collect(),
+                hidden: 0,
+            };
+            let (width, _) = terminal::size().unwrap_or((80, 24));
+            let width = width as usize;
+
+            println!(
+                "{}",
+                board::render(
+                    &[
+                        quadrant(Quadrant::Do, "Do: urgent, important"),
+                        quadrant(Quadrant::Schedule, "Schedule: important"),
+                    ],
+                    width
+                )
+            );
+            println!();
+            println!(
+                "{}",
+                board::render(
+                    &[
+                        quadrant(Quadrant::Delegate, "Delegate: urgent"),
+                        quadrant(Quadrant::Eliminate, "Eliminate: neither"),
+                    ],
+                    width
+                )
+            );
+            println!();
+            println!(
+                "Important: {} weight or above. Urgent: due within {} days or overdue.",
+                matrix.important, matrix.urgent_days
+            );
+        }
+        Command::Calendar { month } => {
+            let today = Local::now().date_naive();
+            let first = month.unwrap_or_else(|| today.with_day(1).expect("day 1 is valid"));
+            let (items, bad_rows) = repo.list(None, false, SortBy::Created, None, None)?;
+            warn_bad_rows(&bad_rows);
+
+            println!("{}", calendar::month(&items, first, today));
+        }
+        Command::Agenda { days } => {
+            let today = Local::now().date_naive();
+            let (items, bad_rows) = repo.list(None, false, SortBy::Created, None, None)?;
+            warn_bad_rows(&bad_rows);
+            let agenda = calendar::agenda(&items, today, days);
+
+            if agenda.overdue.is_empty() && agenda.days.is_empty() {
+                println!("Nothing due or starting in the next {} days", days);
+                return Ok(());
+            }
+
+            if !agenda.overdue.is_empty() {
+                println!("{}", Color::Red.bold().paint("Overdue"));
+                for item in &agenda.overdue {
+                    let overdue = item.time_until_deadline().unwrap_or_else(Duration::zero);
+                    println!(
+                        "  {} ({}, {} overdue)",
+                        item.name,
+                        item.weight,
+                        duration::format(overdue)
+                    );
+                }
+                println!();
+            }
+
+            for (date, events) in &agenda.days {
+                let heading = match (*date - today).num_days() {
+                    0 => format!("Today, {}", date.format("%a %b %d")),
+                    1 => format!("Tomorrow, {}", date.format("%a %b %d")),
+                    _ => date.format("%A, %b %d").to_string(),
+                };
+                println!("{}", Style::new().bold().paint(heading));
+                for (event, item) in events {
+                    let (label, time) = match event {
+                        Event::Due => ("due", item.deadline),
+                        Event::Starts => ("starts", item.start_date),
+                    };
+                    let time = time
+                        .map(|t| t.format("%H:%M").to_string())
+                        .unwrap_or_default();
+                    println!(
+                        "  {} {:<6} {}  {}",
+                        time,
+                        label,
+                        weight_color(item.weight).paint(format!("{:<6}", item.weight.to_string())),
+                        item.name
+                    );
+                }
+                println!();
+            }
+        }
+        Command::Chart {
+            kind,
+            since,
+            until,
+            weight,
+        } => {
+            let until = until.unwrap_or_else(|| Local::now().date_naive());
+            let since = since.unwrap_or(until - Duration::days(29));
+            let (items, bad_rows) = repo.list(weight, false, SortBy::Created, None, None)?;
+            warn_bad_rows(&bad_rows);
+
+            let (width, height) = terminal::size().unwrap_or((80, 24));
+            let height = (height as usize).saturating_sub(8).clamp(4, 16);
+            let (title, series, cumulative) = match kind {
+                ChartKind::Burndown => ("Open tasks", chart::burndown(&items, since, until), true),
+                ChartKind::Throughput => (
+                    "Completed tasks per day",
+                    chart::throughput(&items, since, until),
+                    false,
+                ),
+            };
+            if series.is_empty() {
+                println!("Nothing to chart: --since is after --until");
+                return Ok(());
+            }
+
+            match weight {
+                Some(weight) => println!("{} ({} weight)", title, weight),
+                None => println!("{}", title),
+            }
+            println!();
+            println!(
+                "{}",
+                chart::render(&series, width as usize, height, cumulative)
+            );
+        }
+        Command::Stats {
+            since,
+            until,
+            format,
+        } => {
+            let (items, bad_rows) = repo.list(None, false, SortBy::Created, None, None)?;
+            warn_bad_rows(&bad_rows);
+            let stats = stats::compute(&items, since, until, Local::now().date_naive());
+
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&stats)?),
+                OutputFormat::Text => print_stats(&stats),
+            }
+        }
+        Command::Export {
+            format,
+            output,
+            columns,
+            group_by,
+        } => {
+            let (mut items, bad_rows) = repo.list(None, false, SortBy::Created, None, None)?;
+            warn_bad_rows(&bad_rows);
+            items.sort_by_key(|item| item.id);
+            let with_notes = || {
+                items
+                    .iter()
+                    .map(|item| Ok((item, repo.notes(&item.name)?)))
+                    .collect::<Result<Vec<_>>>()
+            };
+
+            let text = match format {
+                FileFormat::Json => formats::json::export(&with_notes()?)?,
+                FileFormat::Csv => formats::csv::export(&items, &columns)?,
+                FileFormat::TodoTxt => formats::todotxt::export(&items),
+                FileFormat::Ics => formats::ics::export(&items),
+                FileFormat::Markdown => formats::markdown::export(&items, group_by),
+                FileFormat::Org => formats::org::export(&items),
+                FileFormat::Taskwarrior => {
+                    let (text, approximated) = formats::taskwarrior::export(&with_notes()?)?;
+                    for (field, count) in approximated {
+                        eprintln!("note: exported {} for {} tasks", field, count);
+                    }
+                    text
+                }
+            };
+            match output {
+                Some(path) => {
+                    fs::write(&path, text)?;
+                    println!("✓ Exported {} tasks to: {}", items.len(), path.display());
+                }
+                None => println!("{}", text.trim_end()),
+            }
+        }
+        Command::Import {
+            file,
+            format,
+            on_conflict,
+            dry_run,
+            map,
+        } => {
+            let text = fs::read_to_string(&file)?;
+            let mut unmapped = BTreeMap::new();
+            let (items, errors) = match format {
+                FileFormat::Json => (formats::json::import(&text)?, Vec::new()),
+                FileFormat::Csv => {
+                    let (items, errors) = formats::csv::import(&text, &map)?;
+                    (formats::without_notes(items), errors)
+                }
+                FileFormat::TodoTxt => {
+                    let (items, errors) = formats::todotxt::import(&text);
+                    (formats::without_notes(items), errors)
+                }
+                FileFormat::Ics => {
+                    let (items, errors) = formats::ics::import(&text);
+                    (formats::without_notes(items), errors)
+                }
+                FileFormat::Markdown => {
+                    let (items, errors) = formats::markdown::import(&text);
+                    (formats::without_notes(items), errors)
+                }
+                FileFormat::Org => {
+                    let (items, errors) = formats::org::import(&text);
+                    (formats::without_notes(items), errors)
+                }
+                FileFormat::Taskwarrior => {
+                    let import = formats::taskwarrior::import(&text)?;
+                    unmapped = import.unmapped;
+                    (import.items, import.errors)
+                }
+            };
+            let report = repo.import(items, on_conflict, dry_run)?;
+
+            if dry_run {
+                println!("Dry run: importing {} would", file.display());
+            } else {
+                println!("✓ Imported: {}", file.display());
+            }
+            for name in &report.added {
+                println!("  + add: {}", name);
+            }
+            for name in &report.overwritten {
+                println!("  ~ overwrite: {}", name);
+            }
+            for (name, renamed) in &report.renamed {
+                println!("  + add: {} as {}", name, renamed);
+            }
+            for name in &report.skipped {
+                println!("  - skip: {} (name already exists)", name);
+            }
+            for error in &errors {
+                println!("  ✗ error: {}", error);
+            }
+            for (field, count) in &unmapped {
+                println!("  ? not mapped: {} ({} tasks)", field, count);
+            }
+            println!(
+                "  {} added, {} overwritten, {} renamed, {} skipped, {} invalid",
+                report.added.len(),
+                report.overwritten.len(),
+                report.renamed.len(),
+                report.skipped.len(),
+                errors.len()
+            );
+        }
+        Command::Backup { path } => {
+            repo.backup(&path)?;
+            println!("✓ Backed up to: {}", path.display());
+        }
+        Command::Restore { path } => {
+            let previous = repo.restore(&path)?;
+            println!("✓ Restored from: {}", path.display());
+            println!("  Previous database saved to: {}", previous.display());
+        }
+        Command::Doctor { repair } => {
+            let checkup = repo.check()?;
+
+            if checkup.integrity.is_empty() {
+                println!("✓ Integrity check passed");
+            } else {
+                for message in &checkup.integrity {
+                    println!("✗ integrity: {}", message);
+                }
+                match repo.backups()?.first() {
+                    Some(latest) => println!(
+                        "  The database is damaged; the latest backup is: {}",
+                        latest.display()
+                    ),
+                    None => println!("  The database is damaged and there are no backups"),
+                }
+                println!("  Restore a backup with: todo restore <path>");
+            }
+
+            if checkup.problems.is_empty() {
+                println!("✓ Every row can be read");
+            } else {
+                for problem in &checkup.problems {
+                    println!("✗ {}", problem);
+                }
+                if repair {
+                    let backup = repo.repair(&checkup.problems)?;
+                    println!("✓ Repaired {} problems", checkup.problems.len());
+                    println!("  Previous database saved to: {}", backup.display());
+                } else {
+                    println!(
+                        "  {} problems found; fix them with: todo doctor --repair",
+                        checkup.problems.len()
+                    );
+                }
+            }
+        }
         Command::List {
             weight,
             completed,
+            sort,
             sort_by_deadline,
             sort_by_weight,
+            sort_by_estimate,
+            min_estimate,
+            max_estimate,
+            quadrant,
         } => {
-            let items = repo.list(weight, completed, sort_by_deadline, sort_by_weight)?;
+            let sort = match sort {
+                Some(sort) => sort,
+                None if sort_by_deadline => SortKey::Deadline,
+                None if sort_by_weight => SortKey::Weight,
+                None if sort_by_estimate => SortKey::Estimate,
+                None => SortKey::Created,
+            };
+            let sort_by = match sort {
+                SortKey::Deadline => SortBy::Deadline,
+                SortKey::Weight => SortBy::Weight,
+                SortKey::Estimate => SortBy::Estimate,
+                SortKey::Created | SortKey::Urgency => SortBy::Created,
+            };
+            let (mut items, bad_rows) =
+                repo.list(weight, completed, sort_by, min_estimate, max_estimate)?;
+            warn_bad_rows(&bad_rows);
+            if sort == SortKey::Urgency {
+                let config = Config::load()?;
+                let now = Local::now();
+                let mut scored: Vec<_> = items
+                    .into_iter()
+                    .map(|item| {
+                        let score = config.urgency.score(&item, &config.workflow, now);
+                        (score.total, item)
+                    })
+                    .collect();
+                scored.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+                items = scored.into_iter().map(|(_, item)| item).collect();
+            }
+            if let Some(quadrant) = quadrant {
+                let matrix = Config::load()?.matrix;
+                let now = Local::now();
+                items.retain(|item| matrix.quadrant(item, now) == Some(quadrant));
+            }
             if items.is_empty() {
                 println!("No tasks");
                 if let Some(w) = weight {
@@ -111,16 +970,39 @@ fn execute_command(repo: &mut Repo, command: Command) -> Result<()> {
                     if completed { "completed" } else { "pending" }
                 );
             }
-            if sort_by_deadline {
-                println!("  Sorted by: deadline");
-            } else if sort_by_weight {
-                println!("  Sorted by: weight");
+            if let Some(min) = min_estimate {
+                println!("  Min estimate: {}", min);
+            }
+            if let Some(max) = max_estimate {
+                println!("  Max estimate: {}", max);
+            }
+            if let Some(quadrant) = quadrant {
+                println!("  Quadrant: {}", quadrant);
+            }
+            match sort {
+                SortKey::Deadline => println!("  Sorted by: deadline"),
+                SortKey::Weight => println!("  Sorted by: weight"),
+                SortKey::Estimate => println!("  Sorted by: estimate"),
+                SortKey::Urgency => println!("  Sorted by: urgency"),
+                SortKey::Created => {}
+            }
+            let (minutes, points) = remaining_estimate(&items);
+            if minutes > 0 || points > 0 {
+                println!(
+                    "  Remaining estimate: {}",
+                    format_estimate_total(minutes, points)
+                );
             }
             println!();
 
             // Print tasks
+            let (tracked, bad_entries) = repo.tracked()?;
+            warn_bad_rows(&bad_entries);
             for item in items {
                 println!("{}", item);
+                if let Some(tracked) = tracked.get(&item.id) {
+                    println!("    Tracked: {}", duration::format(*tracked));
+                }
                 println!("{}", str::repeat("─", 40));
             }
         }
@@ -129,14 +1011,55 @@ fn execute_command(repo: &mut Repo, command: Command) -> Result<()> {
 }
 
 fn run_repl(repo: &mut Repo) -> Result<()> {
-    let mut line_editor = Reedline::create().with_hinter(Box::new(
-        DefaultHinter::default().with_style(Style::new().italic().fg(Color::LightGray)),
-    ));
-    let prompt = TodoPrompt;
+    let config = Config::load().unwrap_or_else(|e| {
+        eprintln!("warning: {}; using the default settings", e);
+        Config::default()
+    });
+    let tasks = completer::Tasks::default();
+    let mut keybindings = default_emacs_keybindings();
+    keybindings.add_binding(
+        KeyModifiers::NONE,
+        KeyCode::Tab,
+        ReedlineEvent::UntilFound(vec![
+            ReedlineEvent::Menu("completion_menu".to_string()),
+            ReedlineEvent::MenuNext,
+        ]),
+    );
+
+    let mut line_editor = Reedline::create()
+        .with_hinter(Box::new(
+            DefaultHinter::default().with_style(Style::new().italic().fg(Color::LightGray)),
+        ))
+        .with_highlighter(Box::new(TodoHighlighter::new(tasks.clone())))
+        .with_validator(Box::new(TodoValidator))
+        .with_completer(Box::new(TodoCompleter::new(
+            tasks.clone(),
+            config.workflow.statuses,
+        )))
+        .with_menu(ReedlineMenu::EngineCompleter(Box::new(
+            ColumnarMenu::default().with_name("completion_menu"),
+        )))
+        .with_edit_mode(Box::new(Emacs::new(keybindings)))
+        .with_history_exclusion_prefix(Some(" ".to_string()));
+    match history::open(config.history.size) {
+        Ok(history) => line_editor = line_editor.with_history(Box::new(history)),
+        Err(e) => eprintln!("warning: {}; history won't be saved", e),
+    }
+    let mut prompt = TodoPrompt::new(config.prompt.left, config.prompt.right);
 
     loop {
+        prompt.timer = repo.active_timer().unwrap_or_default();
+        if let Ok((items, _)) = repo.list(None, false, SortBy::Created, None, None) {
+            completer::refresh(&tasks, &items);
+            prompt.count(&items);
+        }
+
         match line_editor.read_line(&prompt) {
             Ok(Signal::Success(buffer)) => {
+                if let Err(e) = line_editor.sync_history() {
+                    eprintln!("warning: couldn't save history: {}", e);
+                }
+
                 // Parse the input line as if it were command line arguments
                 match shlex::split(&buffer) {
                     Some(mut arg_strings) => {
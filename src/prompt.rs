@@ -1,30 +1,140 @@
+use chrono::Local;
 use reedline::{Prompt, PromptEditMode, PromptHistorySearch, PromptHistorySearchStatus};
 use std::borrow::Cow;
 
+use crate::repo::{Item, TimeEntry};
+
+/// The placeholders prompt templates can use: counts of open tasks, open
+/// tasks past their deadline, open tasks due today and completed tasks, and
+/// the running timer if there is one. There is no profile or project
+/// placeholder, as all tasks live in the one database in `~/.todo`.
+pub const PLACEHOLDERS: [&str; 5] = ["open", "overdue", "today", "completed", "timer"];
+
 #[derive(Clone)]
-pub struct TodoPrompt;
+pub struct TodoPrompt {
+    /// The templates of the left and right prompts
+    pub left: String,
+    pub right: String,
+    pub open: usize,
+    pub overdue: usize,
+    pub today: usize,
+    pub completed: usize,
+    /// The running timer
+    pub timer: Option<TimeEntry>,
+}
+
+impl TodoPrompt {
+    pub fn new(left: String, right: String) -> Self {
+        Self {
+            left,
+            right,
+            open: 0,
+            overdue: 0,
+            today: 0,
+            completed: 0,
+            timer: None,
+        }
+    }
+
+    /// Recounts the tasks from `items`.
+    pub fn count(&mut self, items: &[Item]) {
+        let now = Local::now();
+        let open = || items.iter().filter(|item| !item.completed);
+
+        self.open = open().count();
+        self.overdue = open()
+            .filter(|item| item.deadline.is_some_and(|deadline| deadline < now))
+            .count();
+        self.today = open()
+            .filter(|item| {
+                item.deadline
+                    .is_some_and(|deadline| deadline.date_naive() == now.date_naive())
+            })
+            .count();
+        self.completed = items.len() - self.open;
+    }
+
+    fn value(&self, placeholder: &str) -> Option<String> {
+        Some(match placeholder {
+            "open" => self.open.to_string(),
+            "overdue" => self.overdue.to_string(),
+            "today" => self.today.to_string(),
+            "completed" => self.completed.to_string(),
+            "timer" => match &self.timer {
+                Some(timer) => format!(
+                    "⏱ {} (since {})",
+                    timer.name,
+                    timer.started_at.format("%H:%M")
+                ),
+                None => String::new(),
+            },
+            _ => return None,
+        })
+    }
+}
+
+/// Fills the `{placeholder}`s of `template` in with `value`, failing on
+/// unknown or unclosed ones. `{{` and `}}` stand for literal braces.
+pub fn expand(
+    template: &str,
+    value: impl Fn(&str) -> Option<String>,
+) -> std::result::Result<String, String> {
+    let mut expanded = String::new();
+    let mut rest = template;
+
+    while let Some(index) = rest.find(['{', '}']) {
+        expanded.push_str(&rest[..index]);
+        let brace = &rest[index..index + 1];
+        rest = &rest[index + 1..];
+
+        if rest.starts_with(brace) {
+            expanded.push_str(brace);
+            rest = &rest[1..];
+        } else if brace == "}" {
+            return Err(format!("unmatched }} in prompt template: {}", template));
+        } else {
+            let end = rest
+                .find('}')
+                .ok_or_else(|| format!("unclosed {{ in prompt template: {}", template))?;
+            let placeholder = rest[..end].trim();
+            expanded.push_str(&value(placeholder).ok_or_else(|| {
+                format!(
+                    "unknown placeholder {{{}}} in prompt template (expected one of: {})",
+                    placeholder,
+                    PLACEHOLDERS.join(", ")
+                )
+            })?);
+            rest = &rest[end + 1..];
+        }
+    }
+
+    expanded.push_str(rest);
+    Ok(expanded)
+}
 
 impl Prompt for TodoPrompt {
-    fn render_prompt_left(&self) -> Cow<str> {
-        Cow::Borrowed("todo")
+    fn render_prompt_left(&self) -> Cow<'_, str> {
+        let left = expand(&self.left, |placeholder| self.value(placeholder));
+        Cow::Owned(left.unwrap_or_else(|_| "todo".to_string()))
     }
 
-    fn render_prompt_right(&self) -> Cow<str> {
-        Cow::Borrowed("") // No right prompt
+    fn render_prompt_right(&self) -> Cow<'_, str> {
+        let right = expand(&self.right, |placeholder| self.value(placeholder));
+        Cow::Owned(right.unwrap_or_default().trim().to_string())
     }
 
-    fn render_prompt_indicator(&self, _edit_mode: PromptEditMode) -> Cow<str> {
+    fn render_prompt_indicator(&self, _edit_mode: PromptEditMode) -> Cow<'_, str> {
         Cow::Borrowed("> ") // No indicator needed since it's part of left prompt
     }
 
-    fn render_prompt_multiline_indicator(&self) -> Cow<str> {
+    fn render_prompt_multiline_indicator(&self) -> Cow<'_, str> {
         Cow::Borrowed(">> ") // For multiline input
     }
 
     fn render_prompt_history_search_indicator(
         &self,
         history_search: PromptHistorySearch,
-    ) -> Cow<str> {
+    ) -> Cow<'_, str> {
         let prefix = match history_search.status {
             PromptHistorySearchStatus::Passing => "",
             PromptHistorySearchStatus::Failing => "failing ",
@@ -1,21 +1,103 @@
-use std::fmt::Display;
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
 
 use chrono::{DateTime, Duration, Local};
-use rusqlite::{params, types::Type, Connection};
+use rusqlite::{
+    backup::Progress,
+    params,
+    types::{Type, Value, ValueRef},
+    Connection, DatabaseName, OpenFlags, OptionalExtension, Row,
+};
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    cli::Weight,
+    cli::{Conflict, Estimate, Weight},
     error::{Error, Result},
+    formats,
 };
 
+/// Schema migrations, applied in order. `PRAGMA user_version` records how
+/// many of them have already run against a database.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS todos (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE,
+        description TEXT,
+        weight TEXT NOT NULL,
+        start_date TEXT,
+        deadline TEXT,
+        completed BOOLEAN NOT NULL DEFAULT 0,
+        created_at TEXT NOT NULL,
+        completed_at TEXT,
+        CHECK (weight IN ('low', 'medium', 'high'))
+    );
+    CREATE INDEX IF NOT EXISTS idx_todos_name ON todos(name);
+    CREATE INDEX IF NOT EXISTS idx_todos_completed ON todos(completed);
+    CREATE INDEX IF NOT EXISTS idx_todos_deadline ON todos(deadline);",
+    "CREATE TABLE notes (
+        id INTEGER PRIMARY KEY,
+        todo_id INTEGER NOT NULL REFERENCES todos(id) ON DELETE CASCADE,
+        body TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        edited_at TEXT
+    );
+    CREATE INDEX idx_notes_todo_id ON notes(todo_id);",
+    "CREATE TABLE time_entries (
+        id INTEGER PRIMARY KEY,
+        todo_id INTEGER NOT NULL REFERENCES todos(id) ON DELETE CASCADE,
+        started_at TEXT NOT NULL,
+        ended_at TEXT
+    );
+    CREATE INDEX idx_time_entries_todo_id ON time_entries(todo_id);",
+    "ALTER TABLE todos ADD COLUMN estimate_minutes INTEGER;
+    ALTER TABLE todos ADD COLUMN estimate_points INTEGER;",
+    "ALTER TABLE todos ADD COLUMN status TEXT;",
+];
+
+/// The columns of the `todos` table in every schema version, including
+/// databases from before migrations were tracked.
+const BASELINE_COLUMNS: [&str; 9] = [
+    "id",
+    "name",
+    "description",
+    "weight",
+    "start_date",
+    "deadline",
+    "completed",
+    "created_at",
+    "completed_at",
+];
+
+/// How many automatic backups are kept in `~/.todo/backups`.
+const BACKUPS_KEPT: usize = 10;
+
+/// How [`Repo::list`] orders its results.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortBy {
+    #[default]
+    Created,
+    Deadline,
+    Weight,
+    Estimate,
+}
+
 pub struct Repo {
     conn: Connection,
+    dir: PathBuf,
 }
 
 impl Repo {
     pub fn new() -> Result<Self> {
         let home_dir = dirs::home_dir().ok_or(Error::HomeDirNotFound)?;
-        let todo_dir = home_dir.join(".todo");
+        Self::open(home_dir.join(".todo"))
+    }
+
+    /// Opens the database in `todo_dir`, creating both if needed.
+    pub fn open(todo_dir: PathBuf) -> Result<Self> {
         std::fs::create_dir_all(&todo_dir)?;
         let db_path = todo_dir.join("todos.db");
 
@@ -24,27 +106,32 @@ impl Repo {
         conn.pragma_update(None, "journal_mode", "WAL")?;
         conn.pragma_update(None, "foreign_keys", "ON")?;
 
-        conn.execute_batch(
-            "BEGIN;
-            CREATE TABLE IF NOT EXISTS todos (
-                id INTEGER PRIMARY KEY,
-                name TEXT NOT NULL UNIQUE,
-                description TEXT,
-                weight TEXT NOT NULL,
-                start_date TEXT,
-                deadline TEXT,
-                completed BOOLEAN NOT NULL DEFAULT 0,
-                created_at TEXT NOT NULL,
-                completed_at TEXT,
-                CHECK (weight IN ('low', 'medium', 'high'))
-            );
-            CREATE INDEX IF NOT EXISTS idx_todos_name ON todos(name);
-            CREATE INDEX IF NOT EXISTS idx_todos_completed ON todos(completed);
-            CREATE INDEX IF NOT EXISTS idx_todos_deadline ON todos(deadline);
-            COMMIT;",
-        )?;
+        let mut repo = Self {
+            conn,
+            dir: todo_dir,
+        };
+        repo.migrate()?;
+        Ok(repo)
+    }
 
-        Ok(Self { conn })
+    /// Runs the migrations the database hasn't had yet, backing it up first
+    /// unless it is new. Databases from before migrations were tracked have
+    /// version 0 but already hold a `todos` table, so they are backed up too.
+    fn migrate(&mut self) -> Result<()> {
+        let version: usize = self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version < MIGRATIONS.len() && has_table(&self.conn, "todos")? {
+            self.rotate_backup("migration")?;
+        }
+
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            let tx = self.conn.transaction()?;
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", index + 1)?;
+            tx.commit()?;
+        }
+        Ok(())
     }
 
     pub fn add(
@@ -54,24 +141,36 @@ impl Repo {
         weight: Option<Weight>,
         days_to_start: Option<u32>,
         days_to_complete: Option<u32>,
+        estimate: Option<Estimate>,
     ) -> Result<()> {
-        let start_date = days_to_start.map(|days| Local::now() + Duration::days(days as i64));
-
-        let deadline = days_to_complete.map(|days| Local::now() + Duration::days(days as i64));
+        self.insert(&Draft {
+            name,
+            description,
+            weight: weight.unwrap_or(Weight::Medium),
+            start_date: days_to_start.map(days_from_now),
+            deadline: days_to_complete.map(days_from_now),
+            estimate,
+        })
+    }
 
+    pub fn insert(&mut self, draft: &Draft) -> Result<()> {
+        let (estimate_minutes, estimate_points) = estimate_columns(draft.estimate);
         let tx = self.conn.transaction()?;
 
         tx.execute(
             "INSERT INTO todos (
-                name, description, weight, start_date, deadline, completed, created_at
-            ) VALUES (?, ?, ?, ?, ?, 0, ?)",
+                name, description, weight, start_date, deadline, completed, created_at,
+                estimate_minutes, estimate_points
+            ) VALUES (?, ?, ?, ?, ?, 0, ?, ?, ?)",
             params![
-                name,
-                description,
-                weight.unwrap_or(Weight::Medium).to_string(),
-                start_date.map(|d| d.to_rfc3339()),
-                deadline.map(|d| d.to_rfc3339()),
+                draft.name,
+                draft.description,
+                draft.weight.to_string(),
+                draft.start_date.map(|d| d.to_rfc3339()),
+                draft.deadline.map(|d| d.to_rfc3339()),
                 Local::now().to_rfc3339(),
+                estimate_minutes,
+                estimate_points,
             ],
         )?;
 
@@ -92,6 +191,7 @@ impl Repo {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn edit(
         &mut self,
         name: String,
@@ -100,6 +200,7 @@ impl Repo {
         weight: Option<Weight>,
         days_to_start: Option<u32>,
         days_to_complete: Option<u32>,
+        estimate: Option<Estimate>,
     ) -> Result<()> {
         let mut updates = Vec::new();
         let mut params = Vec::new();
@@ -118,13 +219,22 @@ impl Repo {
         }
         if let Some(days) = days_to_start {
             updates.push("start_date = ?");
-            let date = Local::now() + Duration::days(days as i64);
-            params.push(date.to_rfc3339());
+            params.push(days_from_now(days).to_rfc3339());
         }
         if let Some(days) = days_to_complete {
             updates.push("deadline = ?");
-            let date = Local::now() + Duration::days(days as i64);
-            params.push(date.to_rfc3339());
+            params.push(days_from_now(days).to_rfc3339());
+        }
+        match estimate {
+            Some(Estimate::Time(duration)) => {
+                updates.push("estimate_minutes = ?, estimate_points = NULL");
+                params.push(duration.num_minutes().to_string());
+            }
+            Some(Estimate::Points(points)) => {
+                updates.push("estimate_minutes = NULL, estimate_points = ?");
+                params.push(points.to_string());
+            }
+            None => {}
         }
 
         if updates.is_empty() {
@@ -146,6 +256,36 @@ impl Repo {
         Ok(())
     }
 
+    /// Overwrites every user-editable field of the named item with `draft`.
+    pub fn update(&mut self, name: &str, draft: &Draft) -> Result<()> {
+        let (estimate_minutes, estimate_points) = estimate_columns(draft.estimate);
+        let tx = self.conn.transaction()?;
+
+        let rows_affected = tx.execute(
+            "UPDATE todos
+            SET name = ?, description = ?, weight = ?, start_date = ?, deadline = ?,
+                estimate_minutes = ?, estimate_points = ?
+            WHERE name = ?",
+            params![
+                draft.name,
+                draft.description,
+                draft.weight.to_string(),
+                draft.start_date.map(|d| d.to_rfc3339()),
+                draft.deadline.map(|d| d.to_rfc3339()),
+                estimate_minutes,
+                estimate_points,
+                name,
+            ],
+        )?;
+
+        if rows_affected == 0 {
+            return Err(Error::ItemNotFound(name.to_string()));
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
     pub fn complete(&mut self, name: &str) -> Result<()> {
         let tx = self.conn.transaction()?;
 
@@ -155,7 +295,7 @@ impl Repo {
                 params![name],
                 |row| row.get::<_, bool>(0),
             )
-            .map_or(false, |completed| !completed);
+            .is_ok_and(|completed| !completed);
 
         if !exists {
             return Err(Error::ItemNotFound(name.to_string()));
@@ -170,13 +310,30 @@ impl Repo {
         Ok(())
     }
 
+    /// Moves the item to a workflow status. Moving it to the `done` status
+    /// completes it; moving it anywhere else reopens it.
+    pub fn set_status(&mut self, key: &str, status: &str, done: bool) -> Result<Item> {
+        let item = self.get(key)?;
+
+        self.conn.execute(
+            "UPDATE todos
+            SET status = ?, completed = ?,
+                completed_at = CASE WHEN ? THEN COALESCE(completed_at, ?) END
+            WHERE id = ?",
+            params![status, done, done, Local::now().to_rfc3339(), item.id],
+        )?;
+
+        self.get_by_id(item.id)
+    }
+
     pub fn list(
         &self,
         weight: Option<Weight>,
         completed: bool,
-        sort_by_deadline: bool,
-        sort_by_weight: bool,
-    ) -> Result<Vec<Item>> {
+        sort: SortBy,
+        min_estimate: Option<Estimate>,
+        max_estimate: Option<Estimate>,
+    ) -> Result<(Vec<Item>, Vec<BadRow>)> {
         let mut query = String::from("SELECT * FROM todos WHERE 1=1");
         let mut params: Vec<String> = Vec::new();
 
@@ -189,70 +346,793 @@ impl Repo {
             query.push_str(" AND completed = 1");
         }
 
+        for (estimate, op) in [(min_estimate, ">="), (max_estimate, "<=")] {
+            match estimate {
+                Some(Estimate::Time(duration)) => {
+                    query.push_str(&format!(" AND estimate_minutes {} ?", op));
+                    params.push(duration.num_minutes().to_string());
+                }
+                Some(Estimate::Points(points)) => {
+                    query.push_str(&format!(" AND estimate_points {} ?", op));
+                    params.push(points.to_string());
+                }
+                None => {}
+            }
+        }
+
         query.push_str(" ORDER BY ");
-        if sort_by_deadline {
-            query.push_str("COALESCE(deadline, '9999-12-31T23:59:59Z')");
-        } else if sort_by_weight {
-            query.push_str(
+        match sort {
+            SortBy::Deadline => query.push_str("COALESCE(deadline, '9999-12-31T23:59:59Z')"),
+            SortBy::Weight => query.push_str(
                 "CASE weight 
                 WHEN 'high' THEN 1 
                 WHEN 'medium' THEN 2 
                 WHEN 'low' THEN 3 
                 END",
-            );
-        } else {
-            query.push_str("created_at DESC");
+            ),
+            // Time estimates first, then story points, then unestimated items
+            SortBy::Estimate => query.push_str(
+                "estimate_minutes IS NULL, estimate_minutes,
+                estimate_points IS NULL, estimate_points",
+            ),
+            SortBy::Created => query.push_str("created_at DESC"),
         }
 
+        // A row that can't be decoded is set aside instead of failing the
+        // whole list, so one bad value doesn't hide every other item
         let mut stmt = self.conn.prepare(&query)?;
-        let todo_iter = stmt.query_map(rusqlite::params_from_iter(params), |row| {
-            Ok(Item {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                description: row.get(2)?,
-                weight: match row.get::<_, String>(3)?.as_str() {
-                    "low" => Weight::Low,
-                    "medium" => Weight::Medium,
-                    "high" => Weight::High,
-                    w => {
-                        return Err(rusqlite::Error::InvalidColumnType(
-                            3,
-                            format!("Invalid weight value: {}", w),
-                            Type::Text,
-                        ))
-                    }
-                },
-                start_date: row
-                    .get::<_, Option<String>>(4)?
-                    .map(|d| DateTime::parse_from_rfc3339(&d).map_err(map_chrono_error(4)))
-                    .transpose()?
-                    .map(|d| d.with_timezone(&Local)),
-                deadline: row
-                    .get::<_, Option<String>>(5)?
-                    .map(|d| DateTime::parse_from_rfc3339(&d).map_err(map_chrono_error(5)))
-                    .transpose()?
-                    .map(|d| d.with_timezone(&Local)),
-                completed: row.get(6)?,
-                created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(7)?)
-                    .map_err(map_chrono_error(7))?
+        let mut rows = stmt.query(rusqlite::params_from_iter(params))?;
+
+        let mut todos = Vec::new();
+        let mut bad_rows = Vec::new();
+        while let Some(row) = rows.next()? {
+            match Item::from_row(row) {
+                Ok(todo) => todos.push(todo),
+                Err(e) => bad_rows.push(BadRow {
+                    table: "todos",
+                    id: row.get(0)?,
+                    message: describe_error(row, e),
+                }),
+            }
+        }
+
+        Ok((todos, bad_rows))
+    }
+
+    /// Looks up an item by name, or by id if no item has `key` as its name.
+    pub fn get(&self, key: &str) -> Result<Item> {
+        self.conn
+            .query_row(
+                "SELECT * FROM todos WHERE name = ?1 OR id = ?2
+                ORDER BY name = ?1 DESC
+                LIMIT 1",
+                params![key, key.parse::<i64>().ok()],
+                Item::from_row,
+            )
+            .optional()?
+            .ok_or_else(|| Error::ItemNotFound(key.to_string()))
+    }
+
+    /// Looks up an item by id only, for callers that already hold one and
+    /// mustn't pick up an item whose name happens to be that number.
+    pub fn get_by_id(&self, id: i64) -> Result<Item> {
+        self.conn
+            .query_row("SELECT * FROM todos WHERE id = ?", [id], Item::from_row)
+            .optional()?
+            .ok_or_else(|| Error::ItemNotFound(id.to_string()))
+    }
+
+    pub fn add_note(&mut self, name: &str, body: String) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+
+        let todo_id = todo_id(&tx, name)?;
+        tx.execute(
+            "INSERT INTO notes (todo_id, body, created_at) VALUES (?, ?, ?)",
+            params![todo_id, body, Local::now().to_rfc3339()],
+        )?;
+        let count: usize = tx.query_row(
+            "SELECT COUNT(*) FROM notes WHERE todo_id = ?",
+            params![todo_id],
+            |row| row.get(0),
+        )?;
+
+        tx.commit()?;
+        Ok(count)
+    }
+
+    pub fn notes(&self, name: &str) -> Result<Vec<Note>> {
+        let todo_id = todo_id(&self.conn, name)?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT body, created_at, edited_at FROM notes
+            WHERE todo_id = ?
+            ORDER BY created_at, id",
+        )?;
+        let note_iter = stmt.query_map(params![todo_id], |row| {
+            Ok(Note {
+                body: row.get(0)?,
+                created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(1)?)
+                    .map_err(map_chrono_error(1))?
                     .with_timezone(&Local),
-                completed_at: row
-                    .get::<_, Option<String>>(8)?
-                    .map(|d| DateTime::parse_from_rfc3339(&d).map_err(map_chrono_error(8)))
+                edited_at: row
+                    .get::<_, Option<String>>(2)?
+                    .map(|d| DateTime::parse_from_rfc3339(&d).map_err(map_chrono_error(2)))
                     .transpose()?
                     .map(|d| d.with_timezone(&Local)),
             })
         })?;
 
-        let mut todos = Vec::new();
-        for todo in todo_iter {
-            todos.push(todo?);
+        let mut notes = Vec::new();
+        for note in note_iter {
+            notes.push(note?);
+        }
+
+        Ok(notes)
+    }
+
+    pub fn edit_note(&mut self, name: &str, index: usize, body: String) -> Result<()> {
+        let tx = self.conn.transaction()?;
+
+        let note_id = note_id(&tx, name, index)?;
+        tx.execute(
+            "UPDATE notes SET body = ?, edited_at = ? WHERE id = ?",
+            params![body, Local::now().to_rfc3339(), note_id],
+        )?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn remove_note(&mut self, name: &str, index: usize) -> Result<()> {
+        let tx = self.conn.transaction()?;
+
+        let note_id = note_id(&tx, name, index)?;
+        tx.execute("DELETE FROM notes WHERE id = ?", params![note_id])?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Imports items with every field intact, along with their notes, resolving
+    /// name clashes with `on_conflict`. An item keeps its `id` unless another
+    /// item already has it (or it is 0), in which case it gets a fresh one.
+    /// With `dry_run` the import is rolled back, so the report shows what
+    /// would have changed.
+    pub fn import(
+        &mut self,
+        items: Vec<(Item, Vec<Note>)>,
+        on_conflict: Conflict,
+        dry_run: bool,
+    ) -> Result<ImportReport> {
+        if !dry_run {
+            self.rotate_backup("import")?;
+        }
+        let tx = self.conn.transaction()?;
+        let mut report = ImportReport::default();
+
+        for (mut item, notes) in items {
+            let existing = match todo_id(&tx, &item.name) {
+                Ok(id) => Some(id),
+                Err(Error::ItemNotFound(_)) => None,
+                Err(e) => return Err(e),
+            };
+
+            match (existing, on_conflict) {
+                (None, _) => {
+                    let id = insert_item(&tx, &item)?;
+                    insert_notes(&tx, id, &notes)?;
+                    report.added.push(item.name);
+                }
+                (Some(_), Conflict::Skip) => report.skipped.push(item.name),
+                (Some(id), Conflict::Overwrite) => {
+                    update_item(&tx, id, &item)?;
+                    insert_notes(&tx, id, &notes)?;
+                    report.overwritten.push(item.name);
+                }
+                (Some(_), Conflict::Rename) => {
+                    let original = item.name.clone();
+                    let mut suffix = 2;
+                    item.name = format!("{} ({})", original, suffix);
+                    while todo_exists(&tx, &item.name)? {
+                        suffix += 1;
+                        item.name = format!("{} ({})", original, suffix);
+                    }
+                    let id = insert_item(&tx, &item)?;
+                    insert_notes(&tx, id, &notes)?;
+                    report.renamed.push((original, item.name));
+                }
+            }
+        }
+
+        if !dry_run {
+            tx.commit()?;
+        }
+        Ok(report)
+    }
+
+    /// Starts a timer on the item. Only one timer can run at a time.
+    pub fn start_timer(&mut self, key: &str) -> Result<TimeEntry> {
+        let item = self.get(key)?;
+        if let Some(running) = self.active_timer()? {
+            return Err(Error::TimerRunning(running.name));
+        }
+
+        let started_at = Local::now();
+        self.conn.execute(
+            "INSERT INTO time_entries (todo_id, started_at) VALUES (?, ?)",
+            params![item.id, started_at.to_rfc3339()],
+        )?;
+
+        Ok(TimeEntry {
+            todo_id: item.id,
+            name: item.name,
+            started_at,
+            ended_at: None,
+        })
+    }
+
+    /// Stops the running timer and returns the finished entry.
+    pub fn stop_timer(&mut self) -> Result<TimeEntry> {
+        let mut running = self.active_timer()?.ok_or(Error::NoTimerRunning)?;
+
+        let ended_at = Local::now();
+        self.conn.execute(
+            "UPDATE time_entries SET ended_at = ? WHERE ended_at IS NULL",
+            params![ended_at.to_rfc3339()],
+        )?;
+
+        running.ended_at = Some(ended_at);
+        Ok(running)
+    }
+
+    pub fn active_timer(&self) -> Result<Option<TimeEntry>> {
+        Ok(self
+            .time_entries()?
+            .0
+            .into_iter()
+            .find(|entry| entry.ended_at.is_none()))
+    }
+
+    /// Records `duration` of work on the item, ending now.
+    pub fn log_time(&mut self, key: &str, duration: Duration) -> Result<TimeEntry> {
+        let item = self.get(key)?;
+
+        let ended_at = Local::now();
+        let started_at = ended_at
+            .checked_sub_signed(duration)
+            .ok_or_else(|| Error::DurationParse(crate::duration::format_exact(duration)))?;
+        self.conn.execute(
+            "INSERT INTO time_entries (todo_id, started_at, ended_at) VALUES (?, ?, ?)",
+            params![item.id, started_at.to_rfc3339(), ended_at.to_rfc3339()],
+        )?;
+
+        Ok(TimeEntry {
+            todo_id: item.id,
+            name: item.name,
+            started_at,
+            ended_at: Some(ended_at),
+        })
+    }
+
+    /// Every time entry, oldest first, and the rows that couldn't be decoded,
+    /// which are set aside like in [`Repo::list`].
+    pub fn time_entries(&self) -> Result<(Vec<TimeEntry>, Vec<BadRow>)> {
+        let mut stmt = self.conn.prepare(
+            "SELECT e.todo_id, t.name, e.started_at, e.ended_at, e.id
+            FROM time_entries e
+            JOIN todos t ON t.id = e.todo_id
+            ORDER BY e.started_at, e.id",
+        )?;
+        let mut rows = stmt.query([])?;
+
+        let mut entries = Vec::new();
+        let mut bad_rows = Vec::new();
+        while let Some(row) = rows.next()? {
+            match TimeEntry::from_row(row) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => bad_rows.push(BadRow {
+                    table: "time_entries",
+                    id: row.get(4)?,
+                    message: describe_error(row, e),
+                }),
+            }
+        }
+
+        Ok((entries, bad_rows))
+    }
+
+    /// Total tracked time per item id, counting a running timer up to now,
+    /// and the time entries left out because they couldn't be decoded.
+    pub fn tracked(&self) -> Result<(HashMap<i64, Duration>, Vec<BadRow>)> {
+        let (entries, bad_rows) = self.time_entries()?;
+        let mut totals = HashMap::new();
+        for entry in entries {
+            *totals.entry(entry.todo_id).or_insert_with(Duration::zero) += entry.duration();
+        }
+        Ok((totals, bad_rows))
+    }
+
+    /// Copies the database to `path` with SQLite's online backup, which is
+    /// consistent even while other connections write to it.
+    pub fn backup(&self, path: &Path) -> Result<()> {
+        self.conn.backup(DatabaseName::Main, path, None)?;
+        Ok(())
+    }
+
+    /// Replaces the database with the one at `path`, after checking that it
+    /// is a todo database this version can read. Returns the automatic backup
+    /// taken of the database it replaced.
+    pub fn restore(&mut self, path: &Path) -> Result<PathBuf> {
+        check_backup(path)?;
+        let previous = self.rotate_backup("restore")?;
+        self.conn
+            .restore(DatabaseName::Main, path, None::<fn(Progress)>)?;
+        self.migrate()?;
+        Ok(previous)
+    }
+
+    /// Backs the database up to `~/.todo/backups`, keeping only the newest
+    /// [`BACKUPS_KEPT`] backups, and returns the new backup's path.
+    pub fn rotate_backup(&self, reason: &str) -> Result<PathBuf> {
+        let dir = self.dir.join("backups");
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!(
+            "todos-{}-{}.db",
+            Local::now().format("%Y%m%d-%H%M%S%3f"),
+            reason
+        ));
+        self.backup(&path)?;
+
+        let backups = self.backups()?;
+        for old in backups.iter().skip(BACKUPS_KEPT) {
+            fs::remove_file(old)?;
+        }
+        Ok(path)
+    }
+
+    /// The automatic backups, newest first.
+    pub fn backups(&self) -> Result<Vec<PathBuf>> {
+        let dir = self.dir.join("backups");
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut backups = fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        backups.retain(|path| path.extension().is_some_and(|ext| ext == "db"));
+        backups.sort();
+        backups.reverse();
+        Ok(backups)
+    }
+
+    /// Runs `PRAGMA integrity_check` and looks for stored values the app
+    /// can't read, such as unknown weights, dates that aren't RFC 3339 and
+    /// rows whose task is gone.
+    pub fn check(&self) -> Result<Checkup> {
+        let mut stmt = self.conn.prepare("PRAGMA integrity_check")?;
+        let integrity = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?
+            .into_iter()
+            // CHECK failures are bad values, reported below as problems
+            .filter(|message| message != "ok" && !message.starts_with("CHECK constraint"))
+            .collect();
+
+        let mut problems = Vec::new();
+        let now = Value::Text(Local::now().to_rfc3339());
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, weight, start_date, deadline, completed, created_at, completed_at,
+                estimate_minutes, estimate_points
+            FROM todos ORDER BY id",
+        )?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let id: i64 = row.get(0)?;
+            let mut problem = |column, value: ValueRef, repair| {
+                problems.push(Problem {
+                    table: "todos",
+                    id,
+                    column,
+                    value: describe(value),
+                    repair,
+                })
+            };
+
+            let weight = row.get_ref(1)?;
+            if !matches!(weight.as_str(), Ok("low" | "medium" | "high")) {
+                problem("weight", weight, Repair::Set(Value::Text("medium".into())));
+            }
+            for (index, column) in [(2, "start_date"), (3, "deadline"), (6, "completed_at")] {
+                let value = row.get_ref(index)?;
+                if !is_date(value, true) {
+                    problem(column, value, Repair::Set(salvage_date(value, Value::Null)));
+                }
+            }
+            let completed = row.get_ref(4)?;
+            if !matches!(completed, ValueRef::Integer(0 | 1)) {
+                problem("completed", completed, Repair::Set(Value::Integer(0)));
+            }
+            let created_at = row.get_ref(5)?;
+            if !is_date(created_at, false) {
+                problem(
+                    "created_at",
+                    created_at,
+                    Repair::Set(salvage_date(created_at, now.clone())),
+                );
+            }
+            let minutes = row.get_ref(7)?;
+            let in_range = match minutes {
+                ValueRef::Null => true,
+                ValueRef::Integer(minutes) => {
+                    minutes >= 0 && Duration::try_minutes(minutes).is_some()
+                }
+                _ => false,
+            };
+            if !in_range {
+                problem("estimate_minutes", minutes, Repair::Set(Value::Null));
+            }
+            let points = row.get_ref(8)?;
+            if !matches!(points, ValueRef::Null | ValueRef::Integer(0..=0xFFFF_FFFF)) {
+                problem("estimate_points", points, Repair::Set(Value::Null));
+            }
+        }
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, created_at, edited_at FROM notes ORDER BY id")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let id: i64 = row.get(0)?;
+            let created_at = row.get_ref(1)?;
+            if !is_date(created_at, false) {
+                problems.push(Problem {
+                    table: "notes",
+                    id,
+                    column: "created_at",
+                    value: describe(created_at),
+                    repair: Repair::Set(salvage_date(created_at, now.clone())),
+                });
+            }
+            let edited_at = row.get_ref(2)?;
+            if !is_date(edited_at, true) {
+                problems.push(Problem {
+                    table: "notes",
+                    id,
+                    column: "edited_at",
+                    value: describe(edited_at),
+                    repair: Repair::Set(salvage_date(edited_at, Value::Null)),
+                });
+            }
+        }
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, started_at, ended_at FROM time_entries ORDER BY id")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let id: i64 = row.get(0)?;
+            for (index, column) in [(1, "started_at"), (2, "ended_at")] {
+                let value = row.get_ref(index)?;
+                if !is_date(value, index == 2) {
+                    let repair = match salvage_date(value, Value::Null) {
+                        Value::Null => Repair::Delete,
+                        date => Repair::Set(date),
+                    };
+                    problems.push(Problem {
+                        table: "time_entries",
+                        id,
+                        column,
+                        value: describe(value),
+                        repair,
+                    });
+                }
+            }
+        }
+
+        let mut stmt = self.conn.prepare("PRAGMA foreign_key_check")?;
+        let orphans = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        for (table, id) in orphans {
+            let table = match table.as_str() {
+                "notes" => "notes",
+                "time_entries" => "time_entries",
+                _ => continue,
+            };
+            problems.push(Problem {
+                table,
+                id,
+                column: "todo_id",
+                value: "a deleted task".to_string(),
+                repair: Repair::Delete,
+            });
+        }
+
+        Ok(Checkup {
+            integrity,
+            problems,
+        })
+    }
+
+    /// Fixes the problems found by [`Repo::check`] in one transaction, after
+    /// taking an automatic backup, which is returned.
+    pub fn repair(&mut self, problems: &[Problem]) -> Result<PathBuf> {
+        let backup = self.rotate_backup("repair")?;
+
+        let tx = self.conn.transaction()?;
+        for problem in problems {
+            match &problem.repair {
+                Repair::Set(value) => tx.execute(
+                    &format!(
+                        "UPDATE {} SET {} = ? WHERE id = ?",
+                        problem.table, problem.column
+                    ),
+                    params![value, problem.id],
+                )?,
+                Repair::Delete => tx.execute(
+                    &format!("DELETE FROM {} WHERE id = ?", problem.table),
+                    params![problem.id],
+                )?,
+            };
         }
+        tx.commit()?;
+
+        Ok(backup)
+    }
+}
+
+/// Names the column a decoding error came from, if it has one.
+fn describe_error(row: &Row, error: rusqlite::Error) -> String {
+    let column = match &error {
+        rusqlite::Error::InvalidColumnType(index, ..)
+        | rusqlite::Error::FromSqlConversionFailure(index, ..)
+        | rusqlite::Error::IntegralValueOutOfRange(index, ..) => {
+            row.as_ref().column_name(*index).ok()
+        }
+        _ => None,
+    };
+
+    match (column, error) {
+        (Some(column), rusqlite::Error::InvalidColumnType(_, message, _)) => {
+            format!("{}: {}", column, message)
+        }
+        (Some(column), error) => format!("{}: {}", column, error),
+        (None, error) => error.to_string(),
+    }
+}
+
+/// Checks that `path` holds a todo database this version can migrate.
+fn check_backup(path: &Path) -> Result<()> {
+    let incompatible =
+        |reason: String| Error::IncompatibleDatabase(format!("{}: {}", path.display(), reason));
+    if !path.is_file() {
+        return Err(incompatible("no such file".to_string()));
+    }
+
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let version: usize = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|e| incompatible(e.to_string()))?;
+    if version > MIGRATIONS.len() {
+        return Err(incompatible(format!(
+            "schema version {}, expected at most {}",
+            version,
+            MIGRATIONS.len()
+        )));
+    }
+
+    // Version 0 is either a database from before migrations were tracked,
+    // which the first migration leaves as it is, or not a todo database at
+    // all; the columns every version has tell them apart
+    if !has_table(&conn, "todos")? {
+        return Err(incompatible("no todos table".to_string()));
+    }
+    let mut stmt = conn.prepare("SELECT name FROM pragma_table_info('todos')")?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let missing: Vec<&str> = BASELINE_COLUMNS
+        .iter()
+        .copied()
+        .filter(|column| !columns.iter().any(|c| c == column))
+        .collect();
+    if !missing.is_empty() {
+        return Err(incompatible(format!(
+            "todos table lacks columns: {}",
+            missing.join(", ")
+        )));
+    }
+
+    let status: String = conn.query_row("PRAGMA quick_check", [], |row| row.get(0))?;
+    if status != "ok" {
+        return Err(incompatible(status));
+    }
+    Ok(())
+}
+
+fn has_table(conn: &Connection, name: &str) -> Result<bool> {
+    Ok(conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?",
+            [name],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some())
+}
+
+/// Whether a stored value is an RFC 3339 date, or NULL where that's allowed.
+fn is_date(value: ValueRef, nullable: bool) -> bool {
+    match value {
+        ValueRef::Null => nullable,
+        ValueRef::Text(text) => {
+            std::str::from_utf8(text).is_ok_and(|text| DateTime::parse_from_rfc3339(text).is_ok())
+        }
+        _ => false,
+    }
+}
+
+/// Rewrites a date stored in another recognizable format as RFC 3339, or
+/// falls back to `default`.
+fn salvage_date(value: ValueRef, default: Value) -> Value {
+    value
+        .as_str()
+        .ok()
+        .and_then(|text| formats::parse_date(text.trim()).ok())
+        .map(|date| Value::Text(date.to_rfc3339()))
+        .unwrap_or(default)
+}
+
+fn describe(value: ValueRef) -> String {
+    match value {
+        ValueRef::Null => "NULL".to_string(),
+        ValueRef::Integer(i) => i.to_string(),
+        ValueRef::Real(f) => f.to_string(),
+        ValueRef::Text(text) => format!("{:?}", String::from_utf8_lossy(text)),
+        ValueRef::Blob(blob) => format!("a {} byte blob", blob.len()),
+    }
+}
+
+fn todo_exists(conn: &Connection, name: &str) -> Result<bool> {
+    Ok(conn
+        .query_row("SELECT 1 FROM todos WHERE name = ?", params![name], |_| {
+            Ok(())
+        })
+        .optional()?
+        .is_some())
+}
+
+/// Inserts a full item and returns the row id it was stored under.
+fn insert_item(conn: &Connection, item: &Item) -> Result<i64> {
+    let id_taken = conn
+        .query_row("SELECT 1 FROM todos WHERE id = ?", params![item.id], |_| {
+            Ok(())
+        })
+        .optional()?
+        .is_some();
+    let id = (item.id > 0 && !id_taken).then_some(item.id);
+    let (estimate_minutes, estimate_points) = estimate_columns(item.estimate);
+
+    conn.execute(
+        "INSERT INTO todos (
+            id, name, description, weight, start_date, deadline, completed, created_at,
+            completed_at, estimate_minutes, estimate_points, status
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        params![
+            id,
+            item.name,
+            item.description,
+            item.weight.to_string(),
+            item.start_date.map(|d| d.to_rfc3339()),
+            item.deadline.map(|d| d.to_rfc3339()),
+            item.completed,
+            item.created_at.to_rfc3339(),
+            item.completed_at.map(|d| d.to_rfc3339()),
+            estimate_minutes,
+            estimate_points,
+            item.status,
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Adds notes to an item, skipping any it already has with the same text and
+/// creation time so that repeated imports don't duplicate them.
+fn insert_notes(conn: &Connection, todo_id: i64, notes: &[Note]) -> Result<()> {
+    for note in notes {
+        conn.execute(
+            "INSERT INTO notes (todo_id, body, created_at, edited_at)
+            SELECT ?1, ?2, ?3, ?4
+            WHERE NOT EXISTS (
+                SELECT 1 FROM notes WHERE todo_id = ?1 AND body = ?2 AND created_at = ?3
+            )",
+            params![
+                todo_id,
+                note.body,
+                note.created_at.to_rfc3339(),
+                note.edited_at.map(|d| d.to_rfc3339()),
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// Overwrites every field of the item with row id `id`, except the id itself.
+fn update_item(conn: &Connection, id: i64, item: &Item) -> Result<()> {
+    let (estimate_minutes, estimate_points) = estimate_columns(item.estimate);
 
-        Ok(todos)
+    conn.execute(
+        "UPDATE todos
+        SET name = ?, description = ?, weight = ?, start_date = ?, deadline = ?,
+            completed = ?, created_at = ?, completed_at = ?,
+            estimate_minutes = ?, estimate_points = ?, status = ?
+        WHERE id = ?",
+        params![
+            item.name,
+            item.description,
+            item.weight.to_string(),
+            item.start_date.map(|d| d.to_rfc3339()),
+            item.deadline.map(|d| d.to_rfc3339()),
+            item.completed,
+            item.created_at.to_rfc3339(),
+            item.completed_at.map(|d| d.to_rfc3339()),
+            estimate_minutes,
+            estimate_points,
+            item.status,
+            id,
+        ],
+    )?;
+    Ok(())
+}
+
+fn estimate_columns(estimate: Option<Estimate>) -> (Option<i64>, Option<u32>) {
+    match estimate {
+        Some(Estimate::Time(duration)) => (Some(duration.num_minutes()), None),
+        Some(Estimate::Points(points)) => (None, Some(points)),
+        None => (None, None),
     }
 }
 
+pub fn days_from_now(days: u32) -> DateTime<Local> {
+    Local::now() + Duration::days(days as i64)
+}
+
+fn todo_id(conn: &Connection, name: &str) -> Result<i64> {
+    conn.query_row(
+        "SELECT id FROM todos WHERE name = ?",
+        params![name],
+        |row| row.get(0),
+    )
+    .optional()?
+    .ok_or_else(|| Error::ItemNotFound(name.to_string()))
+}
+
+/// Resolves a 1-based position in a task's notes timeline to the note's row id.
+fn note_id(conn: &Connection, name: &str, index: usize) -> Result<i64> {
+    let todo_id = todo_id(conn, name)?;
+
+    index
+        .checked_sub(1)
+        .map(|offset| {
+            conn.query_row(
+                "SELECT id FROM notes
+                WHERE todo_id = ?
+                ORDER BY created_at, id
+                LIMIT 1 OFFSET ?",
+                params![todo_id, offset],
+                |row| row.get(0),
+            )
+            .optional()
+        })
+        .transpose()?
+        .flatten()
+        .ok_or_else(|| Error::NoteNotFound(name.to_string(), index))
+}
+
 fn map_chrono_error(column: usize) -> impl Fn(chrono::ParseError) -> rusqlite::Error {
     move |err: chrono::ParseError| {
         rusqlite::Error::InvalidColumnType(
@@ -263,21 +1143,222 @@ fn map_chrono_error(column: usize) -> impl Fn(chrono::ParseError) -> rusqlite::E
     }
 }
 
+/// A row [`Repo::list`] or [`Repo::time_entries`] skipped because it
+/// couldn't be decoded.
 #[derive(Debug)]
+pub struct BadRow {
+    /// `todos` or `time_entries`
+    pub table: &'static str,
+    pub id: i64,
+    pub message: String,
+}
+
+impl Display for BadRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.table {
+            "time_entries" => "time entry",
+            _ => "task",
+        };
+        write!(f, "{} {}: {}", kind, self.id, self.message)
+    }
+}
+
+/// What [`Repo::check`] found.
+#[derive(Debug)]
+pub struct Checkup {
+    /// Messages from `PRAGMA integrity_check`, empty if it passed
+    pub integrity: Vec<String>,
+    pub problems: Vec<Problem>,
+}
+
+/// A stored value the app can't read.
+#[derive(Debug)]
+pub struct Problem {
+    pub table: &'static str,
+    pub id: i64,
+    pub column: &'static str,
+    pub value: String,
+    pub repair: Repair,
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {}: {} ({})",
+            self.table, self.id, self.column, self.value, self.repair
+        )
+    }
+}
+
+/// How [`Repo::repair`] fixes a [`Problem`].
+#[derive(Debug)]
+pub enum Repair {
+    /// Replace the value
+    Set(Value),
+    /// Delete the whole row
+    Delete,
+}
+
+impl Display for Repair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Repair::Set(Value::Null) => write!(f, "repair: clear it"),
+            Repair::Set(Value::Text(text)) => write!(f, "repair: set to {:?}", text),
+            Repair::Set(Value::Integer(i)) => write!(f, "repair: set to {}", i),
+            Repair::Set(value) => write!(f, "repair: set to {:?}", value),
+            Repair::Delete => write!(f, "repair: delete the row"),
+        }
+    }
+}
+
+/// What [`Repo::import`] did, or would do in a dry run, with each item.
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub added: Vec<String>,
+    pub overwritten: Vec<String>,
+    /// Pairs of (imported name, name it was stored under)
+    pub renamed: Vec<(String, String)>,
+    pub skipped: Vec<String>,
+}
+
+/// The user-editable fields of a todo item.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Draft {
+    pub name: String,
+    pub description: Option<String>,
+    pub weight: Weight,
+    pub start_date: Option<DateTime<Local>>,
+    pub deadline: Option<DateTime<Local>>,
+    pub estimate: Option<Estimate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Item {
-    #[allow(dead_code)]
-    id: i64,
-    name: String,
-    description: Option<String>,
-    weight: Weight,
-    start_date: Option<DateTime<Local>>,
-    deadline: Option<DateTime<Local>>,
-    completed: bool,
-    created_at: DateTime<Local>,
-    completed_at: Option<DateTime<Local>>,
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub weight: Weight,
+    pub start_date: Option<DateTime<Local>>,
+    pub deadline: Option<DateTime<Local>>,
+    pub completed: bool,
+    pub created_at: DateTime<Local>,
+    pub completed_at: Option<DateTime<Local>>,
+    pub estimate: Option<Estimate>,
+    /// The workflow status the item was moved to, if any; see
+    /// [`Workflow::status_of`](crate::config::Workflow::status_of)
+    pub status: Option<String>,
 }
 
 impl Item {
+    /// A pending item with default fields, as imports start out from. Its `id`
+    /// is 0, so [`Repo::import`] gives it a fresh one.
+    pub fn new(name: String) -> Self {
+        Item {
+            id: 0,
+            name,
+            description: None,
+            weight: Weight::Medium,
+            start_date: None,
+            deadline: None,
+            completed: false,
+            created_at: Local::now(),
+            completed_at: None,
+            estimate: None,
+            status: None,
+        }
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Item {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            weight: match row.get::<_, String>(3)?.as_str() {
+                "low" => Weight::Low,
+                "medium" => Weight::Medium,
+                "high" => Weight::High,
+                w => {
+                    return Err(rusqlite::Error::InvalidColumnType(
+                        3,
+                        format!("Invalid weight value: {}", w),
+                        Type::Text,
+                    ))
+                }
+            },
+            start_date: row
+                .get::<_, Option<String>>(4)?
+                .map(|d| DateTime::parse_from_rfc3339(&d).map_err(map_chrono_error(4)))
+                .transpose()?
+                .map(|d| d.with_timezone(&Local)),
+            deadline: row
+                .get::<_, Option<String>>(5)?
+                .map(|d| DateTime::parse_from_rfc3339(&d).map_err(map_chrono_error(5)))
+                .transpose()?
+                .map(|d| d.with_timezone(&Local)),
+            completed: row.get(6)?,
+            created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(7)?)
+                .map_err(map_chrono_error(7))?
+                .with_timezone(&Local),
+            completed_at: row
+                .get::<_, Option<String>>(8)?
+                .map(|d| DateTime::parse_from_rfc3339(&d).map_err(map_chrono_error(8)))
+                .transpose()?
+                .map(|d| d.with_timezone(&Local)),
+            estimate: match (row.get::<_, Option<i64>>(9)?, row.get(10)?) {
+                (Some(minutes), _) => Some(Estimate::Time(
+                    Duration::try_minutes(minutes).ok_or_else(|| {
+                        rusqlite::Error::InvalidColumnType(
+                            9,
+                            format!("Estimate out of range: {} minutes", minutes),
+                            Type::Integer,
+                        )
+                    })?,
+                )),
+                (None, Some(points)) => Some(Estimate::Points(points)),
+                (None, None) => None,
+            },
+            status: row.get(11)?,
+        })
+    }
+
+    pub fn draft(&self) -> Draft {
+        Draft {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            weight: self.weight,
+            start_date: self.start_date,
+            deadline: self.deadline,
+            estimate: self.estimate,
+        }
+    }
+
+    /// Time elapsed since the item was created.
+    pub fn age(&self) -> Duration {
+        Local::now() - self.created_at
+    }
+
+    /// Time left until the deadline of a pending item, negative once overdue.
+    pub fn time_until_deadline(&self) -> Option<Duration> {
+        self.deadline
+            .filter(|_| !self.completed)
+            .map(|deadline| deadline - Local::now())
+    }
+
+    /// Time it took from creating the item to completing it.
+    pub fn time_to_completion(&self) -> Option<Duration> {
+        self.completed_at
+            .map(|completed_at| completed_at - self.created_at)
+    }
+
+    /// Time between starting (or creating) the item and completing it.
+    pub fn time_spent(&self) -> Option<Duration> {
+        self.completed_at.map(|completed_at| {
+            let started = self.start_date.filter(|start| *start <= completed_at);
+            completed_at - started.unwrap_or(self.created_at)
+        })
+    }
+
     pub fn format_date(date: Option<DateTime<Local>>) -> String {
         date.map(|d| d.format("%Y-%m-%d %H:%M").to_string())
             .unwrap_or_else(|| "Not set".to_string())
@@ -298,6 +1379,9 @@ impl Display for Item {
             "    Deadline: {}",
             Self::format_date(self.deadline)
         ));
+        if let Some(estimate) = self.estimate {
+            output.push(format!("    Estimate: {}", estimate));
+        }
         output.push(format!(
             "    Created: {}",
             self.created_at.format("%Y-%m-%d %H:%M")
@@ -315,3 +1399,267 @@ impl Display for Item {
         write!(f, "{}", output.join("\n"))
     }
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Note {
+    pub body: String,
+    pub created_at: DateTime<Local>,
+    pub edited_at: Option<DateTime<Local>>,
+}
+
+impl Display for Note {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}  {}",
+            self.created_at.format("%Y-%m-%d %H:%M"),
+            self.body
+        )?;
+        if let Some(edited_at) = self.edited_at {
+            write!(f, " (edited {})", edited_at.format("%Y-%m-%d %H:%M"))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimeEntry {
+    pub todo_id: i64,
+    pub name: String,
+    pub started_at: DateTime<Local>,
+    pub ended_at: Option<DateTime<Local>>,
+}
+
+impl TimeEntry {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(TimeEntry {
+            todo_id: row.get(0)?,
+            name: row.get(1)?,
+            started_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(2)?)
+                .map_err(map_chrono_error(2))?
+                .with_timezone(&Local),
+            ended_at: row
+                .get::<_, Option<String>>(3)?
+                .map(|d| DateTime::parse_from_rfc3339(&d).map_err(map_chrono_error(3)))
+                .transpose()?
+                .map(|d| d.with_timezone(&Local)),
+        })
+    }
+
+    /// Length of the entry, counting a running timer up to now.
+    pub fn duration(&self) -> Duration {
+        self.ended_at.unwrap_or_else(Local::now) - self.started_at
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// A directory under the system temp dir, removed when dropped.
+    pub(crate) struct TempDir(pub PathBuf);
+
+    impl TempDir {
+        pub(crate) fn new() -> Self {
+            static COUNT: AtomicUsize = AtomicUsize::new(0);
+            let path = std::env::temp_dir().join(format!(
+                "todo-test-{}-{}",
+                std::process::id(),
+                COUNT.fetch_add(1, Ordering::Relaxed)
+            ));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// A repo on a fresh database; keep the directory alive while using it.
+    pub(crate) fn temp_repo() -> (Repo, TempDir) {
+        let dir = TempDir::new();
+        (Repo::open(dir.0.clone()).unwrap(), dir)
+    }
+
+    fn add(repo: &mut Repo, name: &str) -> i64 {
+        repo.add(name.to_string(), None, None, None, None, None)
+            .unwrap();
+        repo.get(name).unwrap().id
+    }
+
+    fn names(items: &[Item]) -> Vec<&str> {
+        let mut names: Vec<&str> = items.iter().map(|item| item.name.as_str()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn list_skips_row_with_bad_deadline() {
+        let (mut repo, _dir) = temp_repo();
+        add(&mut repo, "good");
+        let bad = add(&mut repo, "bad");
+        repo.conn
+            .execute(
+                "UPDATE todos SET deadline = 'next tuesday' WHERE id = ?",
+                [bad],
+            )
+            .unwrap();
+
+        let (items, bad_rows) = repo.list(None, false, SortBy::Created, None, None).unwrap();
+        assert_eq!(names(&items), ["good"]);
+        assert_eq!(bad_rows.len(), 1);
+        assert_eq!(bad_rows[0].table, "todos");
+        assert_eq!(bad_rows[0].id, bad);
+        assert!(bad_rows[0].message.starts_with("deadline:"));
+    }
+
+    #[test]
+    fn list_skips_row_with_bad_weight() {
+        let (mut repo, _dir) = temp_repo();
+        let bad = add(&mut repo, "bad");
+        add(&mut repo, "good");
+        repo.conn
+            .pragma_update(None, "ignore_check_constraints", true)
+            .unwrap();
+        repo.conn
+            .execute("UPDATE todos SET weight = 'urgent' WHERE id = ?", [bad])
+            .unwrap();
+
+        let (items, bad_rows) = repo.list(None, false, SortBy::Weight, None, None).unwrap();
+        assert_eq!(names(&items), ["good"]);
+        assert_eq!(bad_rows.len(), 1);
+        assert_eq!(bad_rows[0].id, bad);
+        assert!(bad_rows[0].message.starts_with("weight:"));
+    }
+
+    #[test]
+    fn list_skips_row_with_estimate_out_of_range() {
+        let (mut repo, _dir) = temp_repo();
+        let bad = add(&mut repo, "bad");
+        add(&mut repo, "good");
+        repo.conn
+            .execute(
+                "UPDATE todos SET estimate_minutes = ? WHERE id = ?",
+                [i64::MAX, bad],
+            )
+            .unwrap();
+
+        let (items, bad_rows) = repo.list(None, false, SortBy::Created, None, None).unwrap();
+        assert_eq!(names(&items), ["good"]);
+        assert_eq!(bad_rows[0].id, bad);
+        assert!(bad_rows[0].message.starts_with("estimate_minutes:"));
+        assert_eq!(repo.check().unwrap().problems.len(), 1);
+    }
+
+    #[test]
+    fn tracked_skips_bad_time_entry() {
+        let (mut repo, _dir) = temp_repo();
+        add(&mut repo, "task");
+        repo.log_time("task", Duration::hours(1)).unwrap();
+        repo.log_time("task", Duration::hours(2)).unwrap();
+        let bad: i64 = repo
+            .conn
+            .query_row("SELECT MIN(id) FROM time_entries", [], |row| row.get(0))
+            .unwrap();
+        repo.conn
+            .execute(
+                "UPDATE time_entries SET started_at = 'garbage' WHERE id = ?",
+                [bad],
+            )
+            .unwrap();
+
+        let (tracked, bad_rows) = repo.tracked().unwrap();
+        assert_eq!(
+            tracked.values().copied().collect::<Vec<_>>(),
+            [Duration::hours(2)]
+        );
+        assert_eq!(bad_rows.len(), 1);
+        assert_eq!(bad_rows[0].table, "time_entries");
+        assert_eq!(bad_rows[0].id, bad);
+    }
+
+    #[test]
+    fn log_time_rejects_durations_reaching_before_the_earliest_date() {
+        let (mut repo, _dir) = temp_repo();
+        add(&mut repo, "task");
+
+        let duration = Duration::weeks(20_000_000);
+        assert!(matches!(
+            repo.log_time("task", duration),
+            Err(Error::DurationParse(_))
+        ));
+        assert!(repo.time_entries().unwrap().0.is_empty());
+    }
+
+    /// Writes a database with the schema from before migrations were
+    /// tracked, holding one task.
+    fn baseline_database(path: &Path) {
+        let conn = Connection::open(path).unwrap();
+        conn.execute_batch(MIGRATIONS[0]).unwrap();
+        conn.execute(
+            "INSERT INTO todos (name, weight, created_at) VALUES ('old', 'low', ?)",
+            [Local::now().to_rfc3339()],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn migrating_baseline_database_backs_it_up() {
+        let dir = TempDir::new();
+        baseline_database(&dir.0.join("todos.db"));
+
+        let repo = Repo::open(dir.0.clone()).unwrap();
+        let backups = repo.backups().unwrap();
+        assert_eq!(backups.len(), 1);
+        assert!(backups[0].to_string_lossy().ends_with("-migration.db"));
+        assert_eq!(repo.get("old").unwrap().weight, Weight::Low);
+    }
+
+    #[test]
+    fn new_database_is_not_backed_up() {
+        let (repo, _dir) = temp_repo();
+        assert!(repo.backups().unwrap().is_empty());
+    }
+
+    #[test]
+    fn restores_baseline_database() {
+        let (mut repo, dir) = temp_repo();
+        let path = dir.0.join("old.db");
+        baseline_database(&path);
+
+        repo.restore(&path).unwrap();
+        assert_eq!(repo.get("old").unwrap().name, "old");
+    }
+
+    #[test]
+    fn refuses_to_restore_other_databases() {
+        let (mut repo, dir) = temp_repo();
+        let path = dir.0.join("other.db");
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch("CREATE TABLE todos (id INTEGER PRIMARY KEY, title TEXT)")
+            .unwrap();
+
+        assert!(matches!(
+            repo.restore(&path),
+            Err(Error::IncompatibleDatabase(_))
+        ));
+    }
+
+    #[test]
+    fn get_by_id_ignores_names_that_look_like_ids() {
+        let (mut repo, _dir) = temp_repo();
+        let first = add(&mut repo, "first");
+        let second = add(&mut repo, &first.to_string());
+
+        assert_eq!(repo.get(&first.to_string()).unwrap().id, second);
+        assert_eq!(repo.get_by_id(first).unwrap().name, "first");
+        assert!(matches!(repo.get_by_id(99), Err(Error::ItemNotFound(_))));
+    }
+}
@@ -0,0 +1,157 @@
+//! Productivity statistics over a date window, for the `stats` command.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{Duration, NaiveDate};
+use serde::Serialize;
+
+use crate::{cli::Weight, repo::Item};
+
+#[derive(Debug, Serialize)]
+pub struct Stats {
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+    /// Tasks created and completed per ISO week, e.g. `2024-W51`
+    pub weeks: BTreeMap<String, WeekStats>,
+    /// Time from creation to completion of the tasks completed in the window
+    pub completion_time: BTreeMap<Weight, CompletionTime>,
+    pub deadlines: DeadlineStats,
+    /// Open tasks per weight right now, regardless of the window
+    pub backlog: BTreeMap<Weight, usize>,
+    pub streaks: Streaks,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct WeekStats {
+    pub created: usize,
+    pub completed: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompletionTime {
+    pub completed: usize,
+    pub average_seconds: i64,
+}
+
+/// Completed tasks that had a deadline, split by whether they made it.
+#[derive(Debug, Default, Serialize)]
+pub struct DeadlineStats {
+    pub on_time: usize,
+    pub late: usize,
+    /// Share of them completed on time, from 0 to 1
+    pub on_time_rate: Option<f64>,
+}
+
+/// Runs of consecutive days with at least one completion.
+#[derive(Debug, Default, Serialize)]
+pub struct Streaks {
+    /// The run ending today, or yesterday if nothing is completed yet today
+    pub current_days: usize,
+    pub longest_days: usize,
+    /// The last day of the longest run
+    pub longest_ended: Option<NaiveDate>,
+}
+
+/// Computes the statistics for events from `since` to `until`, both
+/// inclusive, as of `today`.
+pub fn compute(
+    items: &[Item],
+    since: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+    today: NaiveDate,
+) -> Stats {
+    let in_window =
+        |date: NaiveDate| since.is_none_or(|s| date >= s) && until.is_none_or(|u| date <= u);
+
+    let mut weeks: BTreeMap<String, WeekStats> = BTreeMap::new();
+    let mut totals: BTreeMap<Weight, (usize, Duration)> = BTreeMap::new();
+    let mut deadlines = DeadlineStats::default();
+    let mut backlog = BTreeMap::new();
+    let mut days = BTreeSet::new();
+
+    for item in items {
+        if in_window(item.created_at.date_naive()) {
+            weeks
+                .entry(week(item.created_at.date_naive()))
+                .or_default()
+                .created += 1;
+        }
+        if !item.completed {
+            *backlog.entry(item.weight).or_insert(0) += 1;
+            continue;
+        }
+
+        let Some(completed_at) = item.completed_at else {
+            continue;
+        };
+        let completed_on = completed_at.date_naive();
+        if !in_window(completed_on) {
+            continue;
+        }
+
+        weeks.entry(week(completed_on)).or_default().completed += 1;
+        let (count, total) = totals.entry(item.weight).or_insert((0, Duration::zero()));
+        *count += 1;
+        *total += completed_at - item.created_at;
+        match item.deadline {
+            Some(deadline) if completed_at <= deadline => deadlines.on_time += 1,
+            Some(_) => deadlines.late += 1,
+            None => {}
+        }
+        days.insert(completed_on);
+    }
+
+    let with_deadline = deadlines.on_time + deadlines.late;
+    if with_deadline > 0 {
+        deadlines.on_time_rate = Some(deadlines.on_time as f64 / with_deadline as f64);
+    }
+
+    let completion_time = totals
+        .into_iter()
+        .map(|(weight, (count, total))| {
+            let time = CompletionTime {
+                completed: count,
+                average_seconds: total.num_seconds() / count as i64,
+            };
+            (weight, time)
+        })
+        .collect();
+
+    Stats {
+        since,
+        until,
+        weeks,
+        completion_time,
+        deadlines,
+        backlog,
+        streaks: streaks(&days, today),
+    }
+}
+
+fn week(date: NaiveDate) -> String {
+    date.format("%G-W%V").to_string()
+}
+
+fn streaks(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> Streaks {
+    let mut streaks = Streaks::default();
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+
+    for &day in days {
+        run = match previous {
+            Some(previous) if previous.succ_opt() == Some(day) => run + 1,
+            _ => 1,
+        };
+        if run > streaks.longest_days {
+            streaks.longest_days = run;
+            streaks.longest_ended = Some(day);
+        }
+        previous = Some(day);
+    }
+
+    let yesterday = today.pred_opt().unwrap_or(today);
+    if previous.is_some_and(|last| last == today || last == yesterday) {
+        streaks.current_days = run;
+    }
+    streaks
+}
@@ -0,0 +1,310 @@
+//! Reading REPL lines as they are typed: splitting them into words the way
+//! `shlex` will, working out what each word is to the clap definitions, and
+//! with that highlighting the line and holding it open while a quote is
+//! unbalanced.
+
+use clap::{Arg, Command as ClapCommand, CommandFactory};
+use nu_ansi_term::{Color, Style};
+use reedline::{Highlighter, StyledText, ValidationResult, Validator};
+
+use crate::{cli::Args, completer::Tasks};
+
+/// The clap definition of the commands, with the generated `help`
+/// subcommand and `--help` flags included.
+pub fn command() -> ClapCommand {
+    let mut command = Args::command();
+    command.build();
+    command
+}
+
+/// A word of a line, unquoted and unescaped, with its byte span.
+#[derive(Debug, Clone)]
+pub struct Word {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    pub quoted: bool,
+    /// False if the line ends inside a quote or after a backslash
+    pub closed: bool,
+}
+
+/// Splits `line` into words following `shlex` quoting rules.
+pub fn words(line: &str) -> Vec<Word> {
+    let mut words = Vec::new();
+    let mut word: Option<Word> = None;
+    let mut quote = None;
+    let mut escaped = false;
+
+    for (index, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+            if let Some(word) = &mut word {
+                // Inside double quotes, only these characters can be escaped
+                if quote == Some('"') && !matches!(c, '$' | '`' | '"' | '\\' | '\n') {
+                    word.text.push('\\');
+                }
+                word.text.push(c);
+            }
+            continue;
+        }
+        if word.is_none() && c.is_whitespace() {
+            continue;
+        }
+        let current = word.get_or_insert_with(|| Word {
+            start: index,
+            end: index,
+            text: String::new(),
+            quoted: false,
+            closed: true,
+        });
+
+        match (quote, c) {
+            (Some('\''), '\'') | (Some('"'), '"') => quote = None,
+            (Some('\''), c) => current.text.push(c),
+            (_, '\\') => escaped = true,
+            (Some(_), c) => current.text.push(c),
+            (None, '"' | '\'') => {
+                current.quoted = true;
+                quote = Some(c);
+            }
+            (None, c) if c.is_whitespace() => {
+                current.end = index;
+                words.extend(word.take());
+            }
+            (None, c) => current.text.push(c),
+        }
+    }
+
+    if let Some(mut word) = word {
+        word.end = line.len();
+        word.closed = quote.is_none() && !escaped;
+        words.push(word);
+    }
+    words
+}
+
+/// What a word of a line is.
+#[derive(Debug, Clone, Copy)]
+pub enum Role<'a> {
+    /// The subcommand, if it is one
+    Subcommand(Option<&'a ClapCommand>),
+    /// A flag of the subcommand, with its argument if it is one
+    Flag(&'a ClapCommand, Option<&'a Arg>),
+    /// The value of an option or a positional argument of the subcommand
+    Value(&'a ClapCommand, &'a Arg),
+    /// An argument the subcommand doesn't take
+    Extra,
+    /// A word after an unknown subcommand
+    Unknown,
+}
+
+/// Works out the role of each word, given the root command.
+pub fn roles<'a>(root: &'a ClapCommand, words: &[Word]) -> Vec<Role<'a>> {
+    let Some((first, rest)) = words.split_first() else {
+        return Vec::new();
+    };
+    let subcommand = root.find_subcommand(&first.text);
+    let mut roles = vec![Role::Subcommand(subcommand)];
+    let Some(command) = subcommand else {
+        roles.extend(rest.iter().map(|_| Role::Unknown));
+        return roles;
+    };
+
+    let mut pending_option = None;
+    let mut positionals = command.get_positionals();
+    for word in rest {
+        let role = if let Some(arg) = pending_option.take() {
+            Role::Value(command, arg)
+        } else if word.text.starts_with('-') && word.text.len() > 1 && !word.quoted {
+            let flag = word.text.split('=').next().unwrap_or_default();
+            let arg = match flag.strip_prefix("--") {
+                Some(long) => command
+                    .get_arguments()
+                    .find(|arg| arg.get_long() == Some(long)),
+                None => command
+                    .get_arguments()
+                    .find(|arg| arg.get_short().is_some_and(|c| flag[1..] == c.to_string())),
+            };
+            if !word.text.contains('=') {
+                pending_option = arg.filter(|arg| arg.get_action().takes_values());
+            }
+            Role::Flag(command, arg)
+        } else {
+            match positionals.next() {
+                Some(arg) => Role::Value(command, arg),
+                None => Role::Extra,
+            }
+        };
+        roles.push(role);
+    }
+    roles
+}
+
+/// Whether a positional argument takes the name of an existing task.
+pub fn takes_task(command: &ClapCommand, arg: &Arg) -> bool {
+    arg.get_id() == "name" && command.get_name() != "add"
+}
+
+/// The commands that look their task up with [`Repo::get`](crate::repo::Repo::get),
+/// and so also take its ID. The others only match names.
+const TAKES_ID: [&str; 4] = ["show", "start", "log-time", "move"];
+
+/// Whether `command` accepts an ID where it takes a task.
+pub fn takes_id(command: &ClapCommand) -> bool {
+    TAKES_ID.contains(&command.get_name())
+}
+
+/// Colors subcommands, flags, values and quoted strings, and shows in red
+/// the words that won't parse or name no task. The word at the cursor is
+/// only red once nothing it could grow into would fit.
+pub struct TodoHighlighter {
+    command: ClapCommand,
+    tasks: Tasks,
+}
+
+impl TodoHighlighter {
+    pub fn new(tasks: Tasks) -> Self {
+        Self {
+            command: command(),
+            tasks,
+        }
+    }
+
+    fn style(&self, word: &Word, role: Role, typing: bool) -> Style {
+        // Whether `candidates` has the word, or one it could grow into while
+        // the cursor is on it
+        let check = |candidates: &mut dyn Iterator<Item = &str>| {
+            let mut partial = false;
+            for candidate in candidates {
+                if candidate == word.text {
+                    return Some(true);
+                }
+                partial |= typing && candidate.starts_with(&word.text);
+            }
+            partial.then_some(false)
+        };
+        let matched = |style: Style, found: Option<bool>| match found {
+            Some(true) => style,
+            Some(false) => Style::new(),
+            None => Style::new().fg(Color::Red),
+        };
+        let plain = match word.quoted {
+            true => Style::new().fg(Color::Yellow),
+            false => Style::new(),
+        };
+
+        match role {
+            Role::Subcommand(Some(_)) => Style::new().fg(Color::Green).bold(),
+            Role::Subcommand(None) => matched(
+                Style::new(),
+                check(&mut self.command.get_subcommands().map(|c| c.get_name())),
+            ),
+            Role::Flag(_, Some(_)) => Style::new().fg(Color::Cyan),
+            Role::Flag(command, None) => {
+                let flags: Vec<String> = command
+                    .get_arguments()
+                    .filter_map(|arg| arg.get_long())
+                    .map(|long| format!("--{}", long))
+                    .collect();
+                matched(Style::new(), check(&mut flags.iter().map(String::as_str)))
+            }
+            Role::Value(command, arg) => {
+                let values = arg.get_possible_values();
+                if !values.is_empty() {
+                    matched(
+                        Style::new().fg(Color::Purple),
+                        check(&mut values.iter().map(|value| value.get_name())),
+                    )
+                } else if takes_task(command, arg) {
+                    let tasks = self.tasks.lock().expect("no panics while holding the lock");
+                    let ids: Vec<String> = match takes_id(command) {
+                        true => tasks.iter().map(|(id, _)| id.to_string()).collect(),
+                        false => Vec::new(),
+                    };
+                    let mut candidates = tasks
+                        .iter()
+                        .map(|(_, name)| name.as_str())
+                        .chain(ids.iter().map(String::as_str));
+                    matched(plain, check(&mut candidates))
+                } else {
+                    plain
+                }
+            }
+            Role::Extra => Style::new().fg(Color::Red),
+            Role::Unknown => plain,
+        }
+    }
+}
+
+impl Highlighter for TodoHighlighter {
+    fn highlight(&self, line: &str, cursor: usize) -> StyledText {
+        let words = words(line);
+        let roles = roles(&self.command, &words);
+
+        let mut styled = StyledText::new();
+        let mut end = 0;
+        for (word, role) in words.iter().zip(roles) {
+            styled.push((Style::new(), line[end..word.start].to_string()));
+            let typing = word.end == cursor && word.end == line.len();
+            let style = self.style(word, role, typing);
+            styled.push((style, line[word.start..word.end].to_string()));
+            end = word.end;
+        }
+        styled.push((Style::new(), line[end..].to_string()));
+        styled
+    }
+}
+
+/// Keeps the line open, continuing on the next one, while it ends inside a
+/// quote or after a backslash.
+pub struct TodoValidator;
+
+impl Validator for TodoValidator {
+    fn validate(&self, line: &str) -> ValidationResult {
+        match words(line).last() {
+            Some(word) if !word.closed => ValidationResult::Incomplete,
+            _ => ValidationResult::Complete,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The styles of the words of `line`, highlighted with the cursor at its end.
+    fn styles(line: &str) -> Vec<Style> {
+        let tasks = Tasks::default();
+        tasks.lock().unwrap().push((7, "Milk".to_string()));
+        let highlighter = TodoHighlighter::new(tasks);
+        highlighter
+            .highlight(line, line.len())
+            .buffer
+            .into_iter()
+            .filter(|(_, text)| !text.trim().is_empty())
+            .map(|(style, _)| style)
+            .collect()
+    }
+
+    #[test]
+    fn ids_are_valid_where_the_command_resolves_them() {
+        for line in ["show 7", "start 7", "log-time 7 1h", "move 7"] {
+            assert!(
+                !styles(line).contains(&Style::new().fg(Color::Red)),
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn ids_are_invalid_where_the_command_matches_names() {
+        for command in ["remove", "complete", "edit", "note"] {
+            assert_eq!(
+                styles(&format!("{} 7 ", command))[1],
+                Style::new().fg(Color::Red)
+            );
+            assert_eq!(styles(&format!("{} Milk ", command))[1], Style::new());
+        }
+    }
+}
@@ -0,0 +1,620 @@
+//! The full-screen interface of `todo tui`: a task list with a detail pane,
+//! live filtering and sorting, driven through the same [`Repo`] API as the
+//! commands. Drawing only reads [`App`], so it renders on any ratatui backend.
+
+use chrono::Local;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{
+    cli::{SortKey, Weight},
+    config::Config,
+    duration, editor,
+    error::Result,
+    repo::{Draft, Item, Repo, SortBy},
+};
+
+/// What the keyboard currently controls.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    Browse,
+    /// Typing into the filter, which applies as it changes
+    Filter,
+    /// Typing the name of a new task
+    Add(String),
+    /// Waiting for `y` to delete the selected task
+    ConfirmDelete,
+}
+
+/// What the event loop should do after a key press.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Action {
+    Continue,
+    /// Leave the screen to edit the task with this id in the editor
+    Edit(i64),
+    Quit,
+}
+
+pub struct App {
+    config: Config,
+    all: Vec<Item>,
+    /// The items shown, after filtering and sorting
+    items: Vec<Item>,
+    state: ListState,
+    details: Vec<String>,
+    pub filter: String,
+    pub sort: SortKey,
+    pub show_completed: bool,
+    pub mode: Mode,
+    pub message: Option<String>,
+}
+
+impl App {
+    pub fn new(repo: &Repo, config: Config) -> Result<Self> {
+        let mut app = App {
+            config,
+            all: Vec::new(),
+            items: Vec::new(),
+            state: ListState::default(),
+            details: Vec::new(),
+            filter: String::new(),
+            sort: SortKey::Created,
+            show_completed: true,
+            mode: Mode::Browse,
+            message: None,
+        };
+        app.reload(repo)?;
+        Ok(app)
+    }
+
+    /// Reads the items again, keeping the same one selected if it is still
+    /// shown.
+    pub fn reload(&mut self, repo: &Repo) -> Result<()> {
+        let (items, bad_rows) = repo.list(None, false, SortBy::Created, None, None)?;
+        if !bad_rows.is_empty() {
+            self.message = Some(format!(
+                "Skipped {} unreadable tasks; run `todo doctor`",
+                bad_rows.len()
+            ));
+        }
+        self.all = items;
+        self.apply(repo)
+    }
+
+    /// Filters and sorts the items, then refreshes the detail pane.
+    fn apply(&mut self, repo: &Repo) -> Result<()> {
+        let selected = self.selected().map(|item| item.id);
+        let filter = self.filter.to_lowercase();
+
+        let mut items: Vec<Item> = self
+            .all
+            .iter()
+            .filter(|item| self.show_completed || !item.completed)
+            .filter(|item| {
+                filter.is_empty()
+                    || item.name.to_lowercase().contains(&filter)
+                    || item
+                        .description
+                        .as_ref()
+                        .is_some_and(|d| d.to_lowercase().contains(&filter))
+            })
+            .cloned()
+            .collect();
+
+        let now = Local::now();
+        match self.sort {
+            SortKey::Created => {}
+            SortKey::Deadline => items.sort_by_key(|item| (item.deadline.is_none(), item.deadline)),
+            SortKey::Weight => items.sort_by_key(|item| std::cmp::Reverse(item.weight)),
+            SortKey::Estimate => {
+                items.sort_by_key(|item| (item.estimate.is_none(), estimate_key(item)))
+            }
+            SortKey::Urgency => {
+                let urgency = &self.config.urgency;
+                let workflow = &self.config.workflow;
+                items.sort_by(|a, b| {
+                    let a = urgency.score(a, workflow, now).total;
+                    let b = urgency.score(b, workflow, now).total;
+                    b.total_cmp(&a)
+                });
+            }
+        }
+        self.items = items;
+
+        let index = selected
+            .and_then(|id| self.items.iter().position(|item| item.id == id))
+            .or_else(|| (!self.items.is_empty()).then_some(0))
+            .map(|index| index.min(self.items.len().saturating_sub(1)));
+        self.state.select(index);
+        self.refresh_details(repo)
+    }
+
+    fn refresh_details(&mut self, repo: &Repo) -> Result<()> {
+        self.details.clear();
+        let Some(item) = self.selected() else {
+            return Ok(());
+        };
+
+        let item_text = item.to_string();
+        let name = item.name.clone();
+        let id = item.id;
+        self.details.extend(item_text.lines().map(String::from));
+        if let Some(tracked) = repo.tracked()?.0.get(&id) {
+            self.details
+                .push(format!("    Tracked: {}", duration::format(*tracked)));
+        }
+
+        let notes = repo.notes(&name)?;
+        self.details.push(String::new());
+        if notes.is_empty() {
+            self.details.push("No notes".to_string());
+        } else {
+            self.details.push("Notes".to_string());
+            for (index, note) in notes.iter().enumerate() {
+                self.details.push(format!("  {:>2}. {}", index + 1, note));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn selected(&self) -> Option<&Item> {
+        self.state
+            .selected()
+            .and_then(|index| self.items.get(index))
+    }
+
+    /// Updates the app for a key press, making any changes through `repo`.
+    /// Errors from `repo` are shown as messages rather than returned.
+    pub fn handle_key(&mut self, repo: &mut Repo, key: KeyEvent) -> Result<Action> {
+        if key.kind != KeyEventKind::Press {
+            return Ok(Action::Continue);
+        }
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Ok(Action::Quit);
+        }
+
+        self.message = None;
+        let result = match self.mode.clone() {
+            Mode::Browse => self.browse(repo, key.code),
+            Mode::Filter => self.type_filter(repo, key.code),
+            Mode::Add(name) => self.type_name(repo, name, key.code),
+            Mode::ConfirmDelete => self.confirm_delete(repo, key.code),
+        };
+        result.or_else(|e| {
+            self.message = Some(e.to_string());
+            Ok(Action::Continue)
+        })
+    }
+
+    fn browse(&mut self, repo: &mut Repo, code: KeyCode) -> Result<Action> {
+        let last = self.items.len().saturating_sub(1);
+        let index = self.state.selected().unwrap_or(0);
+
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(Action::Quit),
+            KeyCode::Char('j') | KeyCode::Down => self.state.select(Some((index + 1).min(last))),
+            KeyCode::Char('k') | KeyCode::Up => self.state.select(Some(index.saturating_sub(1))),
+            KeyCode::Char('g') | KeyCode::Home => self.state.select(Some(0)),
+            KeyCode::Char('G') | KeyCode::End => self.state.select(Some(last)),
+            KeyCode::Char('/') => self.mode = Mode::Filter,
+            KeyCode::Char('a') => self.mode = Mode::Add(String::new()),
+            KeyCode::Char('d') if self.selected().is_some() => self.mode = Mode::ConfirmDelete,
+            KeyCode::Char('e') => {
+                if let Some(item) = self.selected() {
+                    return Ok(Action::Edit(item.id));
+                }
+            }
+            KeyCode::Char('c') | KeyCode::Char(' ') => match self.selected() {
+                Some(item) if item.completed => {
+                    self.message = Some(format!("Already completed: {}", item.name));
+                }
+                Some(item) => {
+                    let name = item.name.clone();
+                    repo.complete(&name)?;
+                    self.message = Some(format!("Completed: {}", name));
+                    self.reload(repo)?;
+                }
+                None => {}
+            },
+            KeyCode::Char('s') => {
+                self.sort = match self.sort {
+                    SortKey::Created => SortKey::Deadline,
+                    SortKey::Deadline => SortKey::Weight,
+                    SortKey::Weight => SortKey::Estimate,
+                    SortKey::Estimate => SortKey::Urgency,
+                    SortKey::Urgency => SortKey::Created,
+                };
+                self.apply(repo)?;
+            }
+            KeyCode::Char('h') => {
+                self.show_completed = !self.show_completed;
+                self.apply(repo)?;
+            }
+            _ => {}
+        }
+
+        self.refresh_details(repo)?;
+        Ok(Action::Continue)
+    }
+
+    fn type_filter(&mut self, repo: &Repo, code: KeyCode) -> Result<Action> {
+        match code {
+            KeyCode::Enter => self.mode = Mode::Browse,
+            KeyCode::Esc => {
+                self.filter.clear();
+                self.mode = Mode::Browse;
+            }
+            KeyCode::Backspace => {
+                self.filter.pop();
+            }
+            KeyCode::Char(c) => self.filter.push(c),
+            _ => return Ok(Action::Continue),
+        }
+        self.apply(repo)?;
+        Ok(Action::Continue)
+    }
+
+    fn type_name(&mut self, repo: &mut Repo, mut name: String, code: KeyCode) -> Result<Action> {
+        match code {
+            KeyCode::Esc => self.mode = Mode::Browse,
+            KeyCode::Enter if name.trim().is_empty() => self.mode = Mode::Browse,
+            KeyCode::Enter => {
+                self.mode = Mode::Browse;
+                repo.insert(&Draft {
+                    name: name.trim().to_string(),
+                    description: None,
+                    weight: Weight::Medium,
+                    start_date: None,
+                    deadline: None,
+                    estimate: None,
+                })?;
+                self.message = Some(format!("Added: {}", name.trim()));
+                self.reload(repo)?;
+            }
+            KeyCode::Backspace => {
+                name.pop();
+                self.mode = Mode::Add(name);
+            }
+            KeyCode::Char(c) => {
+                name.push(c);
+                self.mode = Mode::Add(name);
+            }
+            _ => {}
+        }
+        Ok(Action::Continue)
+    }
+
+    fn confirm_delete(&mut self, repo: &mut Repo, code: KeyCode) -> Result<Action> {
+        self.mode = Mode::Browse;
+        if let (KeyCode::Char('y'), Some(item)) = (code, self.selected()) {
+            let name = item.name.clone();
+            repo.remove(&name)?;
+            self.message = Some(format!("Removed: {}", name));
+            self.reload(repo)?;
+        }
+        Ok(Action::Continue)
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame) {
+        let [main, footer] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+        let [list_area, detail_area] =
+            Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)])
+                .areas(main);
+
+        let items: Vec<ListItem> = self
+            .items
+            .iter()
+            .map(|item| {
+                let mark = if item.completed { "✓ " } else { "  " };
+                let style = match (item.completed, item.weight) {
+                    (true, _) => Style::new().fg(Color::DarkGray),
+                    (false, Weight::High) => Style::new().fg(Color::Red),
+                    (false, Weight::Medium) => Style::new().fg(Color::Yellow),
+                    (false, Weight::Low) => Style::new().fg(Color::Green),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::raw(mark),
+                    Span::styled(item.name.clone(), style),
+                ]))
+            })
+            .collect();
+
+        // The view settings go along the bottom, where they have room
+        let mut view = format!(" sort: {} ", sort_name(self.sort));
+        if !self.filter.is_empty() {
+            view.push_str(&format!("· filter: {} ", self.filter));
+        }
+        if !self.show_completed {
+            view.push_str("· hiding done ");
+        }
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" Tasks ({}) ", self.items.len()))
+            .title_bottom(view);
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("› ");
+        frame.render_stateful_widget(list, list_area, &mut self.state);
+
+        let details = Paragraph::new(self.details.join("\n"))
+            .block(Block::default().borders(Borders::ALL).title(" Details "))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(details, detail_area);
+
+        let footer_text = match &self.mode {
+            Mode::Filter => format!("/{}█  enter: keep  esc: clear", self.filter),
+            Mode::Add(name) => format!("New task: {}█  enter: add  esc: cancel", name),
+            Mode::ConfirmDelete => format!(
+                "Delete {}? y: yes  any other key: no",
+                self.selected().map_or("", |item| item.name.as_str())
+            ),
+            Mode::Browse => match &self.message {
+                Some(message) => message.clone(),
+                None => "j/k: move  c: complete  a: add  e: edit  d: delete  /: filter  \
+                         s: sort  h: hide done  q: quit"
+                    .to_string(),
+            },
+        };
+        frame.render_widget(
+            Paragraph::new(footer_text).style(Style::new().add_modifier(Modifier::DIM)),
+            footer,
+        );
+    }
+}
+
+/// Runs the interface until the user quits.
+pub fn run(repo: &mut Repo) -> Result<()> {
+    let mut app = App::new(repo, Config::load()?)?;
+    let mut terminal = ratatui::init();
+
+    let result = loop {
+        if let Err(e) = terminal.draw(|frame| app.draw(frame)) {
+            break Err(e.into());
+        }
+        let key = match event::read() {
+            Ok(Event::Key(key)) => key,
+            Ok(_) => continue,
+            Err(e) => break Err(e.into()),
+        };
+
+        match app.handle_key(repo, key) {
+            Ok(Action::Continue) => {}
+            Ok(Action::Quit) => break Ok(()),
+            Ok(Action::Edit(id)) => {
+                ratatui::restore();
+                let edited = edit(repo, id);
+                terminal = ratatui::init();
+                app.message = Some(match edited {
+                    Ok(true) => "Saved".to_string(),
+                    Ok(false) => "Edit cancelled".to_string(),
+                    Err(e) => e.to_string(),
+                });
+                if let Err(e) = app.reload(repo) {
+                    break Err(e);
+                }
+            }
+            Err(e) => break Err(e),
+        }
+    };
+
+    ratatui::restore();
+    result
+}
+
+/// Edits the whole task in the user's editor, like `edit --editor --full`.
+fn edit(repo: &mut Repo, id: i64) -> Result<bool> {
+    let mut draft = repo.get_by_id(id)?.draft();
+    let name = draft.name.clone();
+    editor::edit_draft(&mut draft, true, |draft| repo.update(&name, draft))
+}
+
+fn sort_name(sort: SortKey) -> &'static str {
+    match sort {
+        SortKey::Created => "created",
+        SortKey::Deadline => "deadline",
+        SortKey::Weight => "weight",
+        SortKey::Estimate => "estimate",
+        SortKey::Urgency => "urgency",
+    }
+}
+
+/// Orders time estimates by minutes, then story points.
+fn estimate_key(item: &Item) -> (u8, i64) {
+    match item.estimate {
+        Some(crate::cli::Estimate::Time(duration)) => (0, duration.num_minutes()),
+        Some(crate::cli::Estimate::Points(points)) => (1, points as i64),
+        None => (2, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use super::*;
+    use crate::repo::tests::temp_repo;
+
+    fn add(repo: &mut Repo, name: &str, weight: Weight, days_to_complete: Option<u32>) {
+        repo.add(
+            name.to_string(),
+            None,
+            Some(weight),
+            None,
+            days_to_complete,
+            None,
+        )
+        .unwrap();
+    }
+
+    fn press(app: &mut App, repo: &mut Repo, keys: &str) -> Action {
+        let mut action = Action::Continue;
+        for c in keys.chars() {
+            action = key(app, repo, KeyCode::Char(c));
+        }
+        action
+    }
+
+    fn key(app: &mut App, repo: &mut Repo, code: KeyCode) -> Action {
+        app.handle_key(repo, KeyEvent::from(code)).unwrap()
+    }
+
+    /// Draws the app on an 80x12 test backend and returns its lines.
+    fn render(app: &mut App) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect())
+            .collect()
+    }
+
+    fn screen(app: &mut App) -> String {
+        render(app).join("\n")
+    }
+
+    #[test]
+    fn draws_list_details_and_keys() {
+        let (mut repo, _dir) = temp_repo();
+        add(&mut repo, "Buy milk", Weight::Low, None);
+        repo.add_note("Buy milk", "two liters".to_string()).unwrap();
+        let mut app = App::new(&repo, Config::default()).unwrap();
+
+        let lines = render(&mut app);
+        assert!(lines[0].starts_with("┌ Tasks (1) ─"));
+        assert!(lines[0].contains("┐┌ Details ─"));
+        assert!(lines[10].starts_with("└ sort: created ─"));
+        assert!(lines[1].starts_with("│›   Buy milk "));
+        assert!(lines[1].contains("│[ ] Buy milk (low)"));
+        assert!(lines
+            .iter()
+            .any(|line| line.contains("1. ") && line.contains("two liters")));
+        assert!(lines[11].starts_with("j/k: move  c: complete  a: add"));
+    }
+
+    #[test]
+    fn filters_as_you_type() {
+        let (mut repo, _dir) = temp_repo();
+        add(&mut repo, "Buy milk", Weight::Medium, None);
+        add(&mut repo, "Write report", Weight::High, None);
+        let mut app = App::new(&repo, Config::default()).unwrap();
+
+        press(&mut app, &mut repo, "/MIL");
+        let lines = render(&mut app);
+        assert!(lines[0].starts_with("┌ Tasks (1) ─"));
+        assert!(lines[10].starts_with("└ sort: created · filter: MIL ─"));
+        assert!(lines[1].contains("Buy milk"));
+        assert!(lines[11].starts_with("/MIL█"));
+
+        key(&mut app, &mut repo, KeyCode::Esc);
+        assert!(screen(&mut app).contains("Tasks (2)"));
+        assert_eq!(app.mode, Mode::Browse);
+    }
+
+    #[test]
+    fn sorts_and_moves_the_selection() {
+        let (mut repo, _dir) = temp_repo();
+        add(&mut repo, "Later", Weight::High, Some(9));
+        add(&mut repo, "Sooner", Weight::Low, Some(1));
+        add(&mut repo, "Someday", Weight::Medium, None);
+        let mut app = App::new(&repo, Config::default()).unwrap();
+
+        press(&mut app, &mut repo, "sG");
+        let lines = render(&mut app);
+        assert!(lines[10].starts_with("└ sort: deadline ─"));
+        assert!(lines[1].starts_with("│    Sooner"));
+        assert!(lines[2].starts_with("│    Later"));
+        assert!(lines[3].starts_with("│›   Someday"));
+
+        press(&mut app, &mut repo, "sg");
+        let lines = render(&mut app);
+        assert!(lines[10].starts_with("└ sort: weight ─"));
+        assert!(lines[1].starts_with("│›   Later"));
+        assert!(lines[3].starts_with("│    Sooner"));
+    }
+
+    #[test]
+    fn completes_and_hides_completed() {
+        let (mut repo, _dir) = temp_repo();
+        add(&mut repo, "Buy milk", Weight::Medium, None);
+        let mut app = App::new(&repo, Config::default()).unwrap();
+
+        press(&mut app, &mut repo, "c");
+        assert!(repo.get("Buy milk").unwrap().completed);
+        let lines = render(&mut app);
+        assert!(lines[1].starts_with("│› ✓ Buy milk"));
+        assert!(lines[11].starts_with("Completed: Buy milk"));
+
+        press(&mut app, &mut repo, "c");
+        assert!(screen(&mut app).contains("Already completed: Buy milk"));
+
+        press(&mut app, &mut repo, "h");
+        let lines = render(&mut app);
+        assert!(lines[0].starts_with("┌ Tasks (0) ─"));
+        assert!(lines[10].starts_with("└ sort: created · hiding done ─"));
+    }
+
+    #[test]
+    fn adds_a_task() {
+        let (mut repo, _dir) = temp_repo();
+        let mut app = App::new(&repo, Config::default()).unwrap();
+
+        press(&mut app, &mut repo, "aRead book");
+        assert!(render(&mut app)[11].starts_with("New task: Read book█"));
+        key(&mut app, &mut repo, KeyCode::Enter);
+
+        assert_eq!(repo.get("Read book").unwrap().weight, Weight::Medium);
+        let lines = render(&mut app);
+        assert!(lines[1].starts_with("│›   Read book"));
+        assert!(lines[11].starts_with("Added: Read book"));
+    }
+
+    #[test]
+    fn deletes_only_after_confirming() {
+        let (mut repo, _dir) = temp_repo();
+        add(&mut repo, "Buy milk", Weight::Medium, None);
+        let mut app = App::new(&repo, Config::default()).unwrap();
+
+        press(&mut app, &mut repo, "d");
+        assert!(render(&mut app)[11].starts_with("Delete Buy milk? y: yes"));
+        press(&mut app, &mut repo, "n");
+        assert!(repo.get("Buy milk").is_ok());
+
+        press(&mut app, &mut repo, "dy");
+        assert!(repo.get("Buy milk").is_err());
+        let lines = render(&mut app);
+        assert!(lines[0].starts_with("┌ Tasks (0)"));
+        assert!(lines[11].starts_with("Removed: Buy milk"));
+    }
+
+    #[test]
+    fn shows_errors_in_the_footer() {
+        let (mut repo, _dir) = temp_repo();
+        add(&mut repo, "Buy milk", Weight::Medium, None);
+        let mut app = App::new(&repo, Config::default()).unwrap();
+
+        press(&mut app, &mut repo, "aBuy milk");
+        key(&mut app, &mut repo, KeyCode::Enter);
+        assert!(render(&mut app)[11].starts_with("database error: UNIQUE constraint failed"));
+    }
+
+    #[test]
+    fn edit_and_quit_are_left_to_the_loop() {
+        let (mut repo, _dir) = temp_repo();
+        add(&mut repo, "Buy milk", Weight::Medium, None);
+        let id = repo.get("Buy milk").unwrap().id;
+        let mut app = App::new(&repo, Config::default()).unwrap();
+
+        assert_eq!(press(&mut app, &mut repo, "e"), Action::Edit(id));
+        assert_eq!(press(&mut app, &mut repo, "q"), Action::Quit);
+    }
+}